@@ -0,0 +1,129 @@
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{backend, CoreId, SpinlockMutex, SpinlockMutexGuard};
+
+const NO_OWNER: u8 = u8::MAX;
+
+/// The core currently holding each spinlock through an [`AbortSafeGuard`], or `NO_OWNER`.
+///
+/// Slot `N` is only ever written while spinlock `N` is claimed, so plain loads and stores suffice.
+#[allow(clippy::declare_interior_mutable_const)]
+static OWNERS: [AtomicU8; 32] = {
+    const SLOT: AtomicU8 = AtomicU8::new(NO_OWNER);
+    [SLOT; 32]
+};
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock like [`lock`](Self::lock), but records the claim so that
+    /// [`panic_release_all`] can release it from a panic handler.
+    ///
+    /// With `panic = "abort"` the destructors of live guards never run, so a panic while a
+    /// regular [`SpinlockMutexGuard`] is held leaks the hardware spinlock until the next reset
+    /// (the rp2040 does not even clear spinlocks on a core reset). If the other core, or the
+    /// panicking core after a soft restart, then tries to lock, it deadlocks.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static MUTEX: SpinlockMutex<7, i32> = SpinlockMutex::new(0);
+    ///
+    /// // registered with #[panic_handler]
+    /// fn panic(_info: &core::panic::PanicInfo) -> ! {
+    ///     // SAFETY: The panicking core never returns to the code holding the guards.
+    ///     unsafe { rp_spinlockmutex::panic_release_all() };
+    ///     loop {}
+    /// }
+    ///
+    /// *MUTEX.lock_abort_safe() += 1;
+    /// ```
+    #[inline]
+    pub fn lock_abort_safe(&self) -> AbortSafeGuard<N, T> {
+        let guard = self.lock();
+        OWNERS[N].store(backend::core(), Ordering::Relaxed);
+        AbortSafeGuard { guard }
+    }
+}
+
+/// Returns the core that claimed spinlock `n` through an [`AbortSafeGuard`], if any.
+///
+/// This is racy, as the slot is written by the owning core while the caller may not hold the lock.
+#[inline]
+pub(crate) fn owner(n: usize) -> Option<CoreId> {
+    match OWNERS[n].load(Ordering::Relaxed) {
+        0 => Some(CoreId::Core0),
+        1 => Some(CoreId::Core1),
+        _ => None,
+    }
+}
+
+/// Releases every spinlock the current core holds through an [`AbortSafeGuard`].
+///
+/// This is meant to be called from a `#[panic_handler]` in `panic = "abort"` builds, so that the
+/// other core can keep running or the system can be restarted without leftover claimed locks.
+/// Spinlocks held by the other core, or through plain [`SpinlockMutexGuard`]s, are left untouched.
+///
+/// # Safety
+///
+/// None of the current core's [`AbortSafeGuard`]s may be used after this call. This holds in a
+/// panic handler that never returns, but calling it anywhere else lets the other core acquire a
+/// lock whose data is still being accessed.
+pub unsafe fn panic_release_all() {
+    let core = backend::core();
+
+    for (n, owner) in OWNERS.iter().enumerate() {
+        if owner.load(Ordering::Relaxed) == core {
+            owner.store(NO_OWNER, Ordering::Relaxed);
+            backend::release(n);
+        }
+    }
+}
+
+/// A guard obtained from [`SpinlockMutex::lock_abort_safe`].
+///
+/// It behaves exactly like a [`SpinlockMutexGuard`], but its claim can be released by
+/// [`panic_release_all`] if the guard is never dropped.
+#[must_use = "if unused the SpinlockMutex will immediately unlock"]
+pub struct AbortSafeGuard<const N: usize, T: ?Sized>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    guard: SpinlockMutexGuard<N, T>,
+}
+
+impl<const N: usize, T: ?Sized> Drop for AbortSafeGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        // The inner guard is dropped after this, so the slot is cleared while the lock is still held.
+        OWNERS[N].store(NO_OWNER, Ordering::Relaxed);
+    }
+}
+
+impl<const N: usize, T: ?Sized> Deref for AbortSafeGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<const N: usize, T: ?Sized> DerefMut for AbortSafeGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
@@ -0,0 +1,401 @@
+#[cfg(all(not(target_arch = "arm"), not(feature = "std-testing"), not(loom)))]
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(loom)]
+use loom::sync::atomic::{AtomicBool, Ordering};
+#[cfg(all(not(target_arch = "arm"), feature = "std-testing"))]
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+
+#[cfg(not(target_arch = "arm"))]
+use cortex_m::interrupt::InterruptNumber;
+#[cfg(not(target_arch = "arm"))]
+use rp2040_hal::pac::Interrupt;
+#[cfg(target_arch = "arm")]
+use rp2040_hal::pac::NVIC;
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+#[cfg(target_arch = "arm")]
+use rp2040_hal::{pac, Sio};
+
+#[cfg(all(loom, feature = "std-testing"))]
+compile_error!("the loom model of the host backend does not support the `std-testing` feature");
+
+/// The memory fence of the guards. Under loom, it is the modeled fence, so that loom checks
+/// the guards' memory ordering against the modeled spinlocks.
+#[cfg(not(loom))]
+pub(crate) use core::sync::atomic::fence;
+#[cfg(loom)]
+pub(crate) use loom::sync::atomic::fence;
+
+/// The lock primitive backing a [`SpinlockMutex`](crate::SpinlockMutex).
+///
+/// The backend is selected by the compilation target: on ARM it is the rp2040 hardware
+/// [`Spinlock<N>`], everywhere else it is `HostSpinlock<N>`, an atomic-based replacement
+/// that allows running logic built on this crate in host unit tests (`cargo test` on x86),
+/// where the SIO peripheral does not exist.
+///
+/// Both backends follow the interface of [`Spinlock<N>`]: a value of the implementing type
+/// proves that lock `N` is claimed, and dropping it releases the lock.
+pub(crate) trait LockStrategy: Sized {
+    /// Claims the lock, spinning until it is available.
+    fn claim() -> Self;
+
+    /// Tries to claim the lock, returning `None` if it is held elsewhere.
+    fn try_claim() -> Option<Self>;
+
+    /// Releases the lock without a value proving it is claimed.
+    ///
+    /// # Safety
+    ///
+    /// The lock must be claimed by the caller, and the value obtained when claiming it must
+    /// not be dropped afterwards (e.g. by [`forget`](core::mem::forget)ting it).
+    unsafe fn release();
+}
+
+#[cfg(target_arch = "arm")]
+impl<const N: usize> LockStrategy for Spinlock<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline(always)]
+    fn claim() -> Self {
+        Spinlock::claim()
+    }
+
+    #[inline(always)]
+    fn try_claim() -> Option<Self> {
+        Spinlock::try_claim()
+    }
+
+    #[inline(always)]
+    unsafe fn release() {
+        Spinlock::release()
+    }
+}
+
+/// The lock backend used by this crate on the current target.
+#[cfg(target_arch = "arm")]
+pub(crate) type Lock<const N: usize> = Spinlock<N>;
+#[cfg(not(target_arch = "arm"))]
+pub(crate) type Lock<const N: usize> = HostSpinlock<N>;
+
+#[allow(clippy::declare_interior_mutable_const)]
+#[cfg(all(not(target_arch = "arm"), not(feature = "std-testing"), not(loom)))]
+static HOST_LOCKS: [AtomicBool; 32] = {
+    const UNLOCKED: AtomicBool = AtomicBool::new(false);
+    [UNLOCKED; 32]
+};
+
+// Loom's atomics cannot be created in a const context, and are created anew for each
+// execution of the model.
+#[cfg(loom)]
+loom::lazy_static! {
+    static ref HOST_LOCKS: [AtomicBool; 32] = Default::default();
+}
+
+/// The ordering of claiming and releasing a host lock.
+///
+/// The hardware spinlocks do not order memory accesses, the guards issue fences instead. Under
+/// loom, the host locks are relaxed like the hardware, so the model checks that the fences
+/// are sufficient. Regular host tests order the accesses in the locks as well.
+#[cfg(all(not(target_arch = "arm"), not(feature = "std-testing")))]
+const ORDERING: (Ordering, Ordering) = if cfg!(loom) {
+    (Ordering::Relaxed, Ordering::Relaxed)
+} else {
+    (Ordering::Acquire, Ordering::Release)
+};
+
+/// The claimed locks as a mask, and a condition variable notified whenever a lock is released.
+// Const `Mutex::new` requires Rust 1.63, which the `std-testing` feature documents.
+#[allow(clippy::incompatible_msrv)]
+#[cfg(all(not(target_arch = "arm"), feature = "std-testing"))]
+static HOST_LOCKS: (Mutex<u32>, Condvar) = (Mutex::new(0), Condvar::new());
+
+/// Locks the mask of claimed locks. A panic while it was locked cannot leave it inconsistent,
+/// so poisoning is ignored.
+#[cfg(all(not(target_arch = "arm"), feature = "std-testing"))]
+fn host_locks() -> MutexGuard<'static, u32> {
+    HOST_LOCKS.0.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A replacement for the hardware [`Spinlock<N>`] on targets other than the rp2040.
+///
+/// The 32 locks are emulated by a static array of atomic flags, so just like the hardware
+/// spinlocks they are global: all mutexes using the same number contend for the same lock,
+/// across all threads of the test. The host reports itself as core 0.
+///
+/// With the `std-testing` feature, the locks are instead emulated with a
+/// [`std::sync::Mutex`] and a [`std::sync::Condvar`], so waiting threads sleep instead of
+/// spinning. This keeps tests with many contending threads fast on machines with few cores.
+/// Threads can then also emulate core 1, see [`CoreId::emulate`](crate::CoreId::emulate).
+#[cfg(not(target_arch = "arm"))]
+pub struct HostSpinlock<const N: usize>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    _private: (),
+}
+
+#[cfg(not(target_arch = "arm"))]
+impl<const N: usize> LockStrategy for HostSpinlock<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[cfg(not(feature = "std-testing"))]
+    #[inline]
+    fn claim() -> Self {
+        loop {
+            if let Some(lock) = Self::try_claim() {
+                break lock;
+            }
+            // Loom has to schedule the thread holding the lock to ever make progress.
+            #[cfg(loom)]
+            loom::thread::yield_now();
+            #[cfg(not(loom))]
+            core::hint::spin_loop();
+        }
+    }
+
+    #[cfg(feature = "std-testing")]
+    #[inline]
+    fn claim() -> Self {
+        let mut locks = host_locks();
+        while *locks & 1 << N != 0 {
+            locks = HOST_LOCKS.1.wait(locks).unwrap_or_else(PoisonError::into_inner);
+        }
+        *locks |= 1 << N;
+        Self { _private: () }
+    }
+
+    #[cfg(not(feature = "std-testing"))]
+    #[inline]
+    fn try_claim() -> Option<Self> {
+        HOST_LOCKS[N]
+            .compare_exchange(false, true, ORDERING.0, Ordering::Relaxed)
+            .ok()
+            .map(|_| Self { _private: () })
+    }
+
+    #[cfg(feature = "std-testing")]
+    #[inline]
+    fn try_claim() -> Option<Self> {
+        let mut locks = host_locks();
+        if *locks & 1 << N != 0 {
+            return None;
+        }
+        *locks |= 1 << N;
+        Some(Self { _private: () })
+    }
+
+    #[inline]
+    unsafe fn release() {
+        release(N);
+    }
+}
+
+#[cfg(not(target_arch = "arm"))]
+impl<const N: usize> Drop for HostSpinlock<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: `self` proves that the lock is claimed, and it is not dropped again.
+        unsafe { Self::release() };
+    }
+}
+
+#[cfg(all(not(target_arch = "arm"), feature = "std-testing"))]
+std::thread_local! {
+    /// The core the current thread emulates, see [`CoreId::emulate`](crate::CoreId::emulate).
+    static HOST_CORE: core::cell::Cell<u8> = const { core::cell::Cell::new(0) };
+}
+
+/// Makes the current thread report itself as running on core `core`.
+#[cfg(all(not(target_arch = "arm"), feature = "std-testing"))]
+#[inline]
+pub(crate) fn set_core(core: u8) {
+    HOST_CORE.with(|host_core| host_core.set(core));
+}
+
+/// Returns the number of the core executing this function.
+#[inline(always)]
+pub(crate) fn core() -> u8 {
+    #[cfg(target_arch = "arm")]
+    return Sio::core();
+    #[cfg(all(not(target_arch = "arm"), feature = "std-testing"))]
+    return HOST_CORE.with(core::cell::Cell::get);
+    #[cfg(all(not(target_arch = "arm"), not(feature = "std-testing")))]
+    return 0;
+}
+
+/// Returns a mask where bit `n` is set if lock `n` is claimed.
+#[inline]
+pub(crate) fn locked_mask() -> u32 {
+    #[cfg(target_arch = "arm")]
+    {
+        // SAFETY: We're only reading from this register
+        let sio = unsafe { &*pac::SIO::ptr() };
+        sio.spinlock_st.read().bits()
+    }
+    #[cfg(all(not(target_arch = "arm"), feature = "std-testing"))]
+    {
+        *host_locks()
+    }
+    #[cfg(all(not(target_arch = "arm"), not(feature = "std-testing")))]
+    {
+        HOST_LOCKS
+            .iter()
+            .enumerate()
+            .filter(|(_, lock)| lock.load(Ordering::Relaxed))
+            .fold(0, |mask, (n, _)| mask | 1 << n)
+    }
+}
+
+/// Releases lock `n`, which has to be in the range 0 to 31.
+///
+/// # Safety
+///
+/// See [`LockStrategy::release`].
+#[inline]
+pub(crate) unsafe fn release(n: usize) {
+    #[cfg(target_arch = "arm")]
+    {
+        let sio = &*pac::SIO::ptr();
+        // Write (any value): release the lock
+        sio.spinlock[n].write_with_zero(|b| b.bits(1));
+    }
+    #[cfg(all(not(target_arch = "arm"), feature = "std-testing"))]
+    {
+        *host_locks() &= !(1 << n);
+        HOST_LOCKS.1.notify_all();
+    }
+    #[cfg(all(not(target_arch = "arm"), not(feature = "std-testing")))]
+    HOST_LOCKS[n].store(false, ORDERING.1);
+}
+
+/// The enabled interrupts of each core of the NVIC emulation, as a mask like `ISER`.
+#[cfg(not(target_arch = "arm"))]
+static HOST_NVIC_ENABLED: [core::sync::atomic::AtomicU32; 2] = [
+    core::sync::atomic::AtomicU32::new(0),
+    core::sync::atomic::AtomicU32::new(0),
+];
+
+/// The priorities of the interrupts of the NVIC emulation, shared by both cores.
+#[allow(clippy::declare_interior_mutable_const)]
+#[cfg(not(target_arch = "arm"))]
+static HOST_NVIC_PRIORITIES: [core::sync::atomic::AtomicU8; 32] = {
+    const HIGHEST: core::sync::atomic::AtomicU8 = core::sync::atomic::AtomicU8::new(0);
+    [HIGHEST; 32]
+};
+
+/// An emulation of the NVIC for host tests of [`SpinlockMutex::lock_masking`] and
+/// [`SpinlockMutex::lock_ceiling`].
+///
+/// On targets other than ARM these methods enable and disable interrupts in this emulation,
+/// which tests set up and inspect through the methods here. Like the NVIC of the rp2040, each
+/// core has its own enabled interrupts, see [`CoreId::emulate`](crate::CoreId::emulate), and
+/// all interrupts start disabled with priority 0. Interrupt handlers are never run.
+///
+/// [`SpinlockMutex::lock_masking`]: crate::SpinlockMutex::lock_masking
+/// [`SpinlockMutex::lock_ceiling`]: crate::SpinlockMutex::lock_ceiling
+#[cfg(not(target_arch = "arm"))]
+pub struct HostNvic {
+    _private: (),
+}
+
+#[cfg(not(target_arch = "arm"))]
+impl HostNvic {
+    /// Enables `irq` on the current core.
+    #[inline]
+    pub fn enable(irq: Interrupt) {
+        // SAFETY: There are no mask-based critical sections in the emulation.
+        unsafe { enable_interrupts(1 << irq.number()) };
+    }
+
+    /// Disables `irq` on the current core.
+    #[inline]
+    pub fn disable(irq: Interrupt) {
+        disable_interrupts(1 << irq.number());
+    }
+
+    /// Returns whether `irq` is enabled on the current core.
+    #[inline]
+    pub fn is_enabled(irq: Interrupt) -> bool {
+        enabled_interrupts() & 1 << irq.number() != 0
+    }
+
+    /// Sets the priority of `irq`, in the NVIC encoding where lower values are more urgent.
+    #[inline]
+    pub fn set_priority(irq: Interrupt, priority: u8) {
+        HOST_NVIC_PRIORITIES[usize::from(irq.number())].store(priority, core::sync::atomic::Ordering::Relaxed);
+    }
+
+    /// Restores the state after reset: disables all interrupts on the current core, and sets
+    /// the priorities of all interrupts back to 0.
+    ///
+    /// The emulation is global, so tests that set it up should call this before and after,
+    /// and take turns if they run in parallel.
+    #[inline]
+    pub fn reset() {
+        disable_interrupts(enabled_interrupts());
+        for priority in &HOST_NVIC_PRIORITIES {
+            priority.store(0, core::sync::atomic::Ordering::Relaxed);
+        }
+    }
+}
+
+/// Returns the interrupts enabled on the current core, as a mask like `ISER`.
+#[inline]
+pub(crate) fn enabled_interrupts() -> u32 {
+    #[cfg(target_arch = "arm")]
+    {
+        // SAFETY: Reading the NVIC registers has no side effects.
+        unsafe { (*NVIC::PTR).iser[0].read() }
+    }
+    #[cfg(not(target_arch = "arm"))]
+    HOST_NVIC_ENABLED[usize::from(core())].load(core::sync::atomic::Ordering::Relaxed)
+}
+
+/// Returns the priority of interrupt `irq`, in the NVIC encoding.
+#[inline]
+pub(crate) fn interrupt_priority(irq: usize) -> u8 {
+    #[cfg(target_arch = "arm")]
+    {
+        // SAFETY: Reading the NVIC registers has no side effects.
+        let nvic = unsafe { &*NVIC::PTR };
+        (nvic.ipr[irq / 4].read() >> (8 * (irq % 4))) as u8
+    }
+    #[cfg(not(target_arch = "arm"))]
+    HOST_NVIC_PRIORITIES[irq].load(core::sync::atomic::Ordering::Relaxed)
+}
+
+/// Disables the interrupts in `mask` on the current core, like a write to `ICER`. None of them
+/// is taken anymore once this returns.
+#[inline]
+pub(crate) fn disable_interrupts(mask: u32) {
+    #[cfg(target_arch = "arm")]
+    // SAFETY: Disabling interrupts cannot break a mask-based critical section, and the
+    //         barriers only affect the order of execution.
+    unsafe {
+        (*NVIC::PTR).icer[0].write(mask);
+        core::arch::asm!("dsb", "isb", options(nostack, preserves_flags));
+    }
+    #[cfg(not(target_arch = "arm"))]
+    HOST_NVIC_ENABLED[usize::from(core())].fetch_and(!mask, core::sync::atomic::Ordering::Relaxed);
+    #[cfg(all(feature = "test-trace", not(target_arch = "arm")))]
+    crate::event_log::record_interrupts(mask, false);
+}
+
+/// Enables the interrupts in `mask` on the current core, like a write to `ISER`.
+///
+/// # Safety
+///
+/// This can break mask-based critical sections, see `cortex_m::peripheral::NVIC::unmask`.
+#[inline]
+pub(crate) unsafe fn enable_interrupts(mask: u32) {
+    #[cfg(target_arch = "arm")]
+    (*NVIC::PTR).iser[0].write(mask);
+    #[cfg(not(target_arch = "arm"))]
+    HOST_NVIC_ENABLED[usize::from(core())].fetch_or(mask, core::sync::atomic::Ordering::Relaxed);
+    #[cfg(all(feature = "test-trace", not(target_arch = "arm")))]
+    crate::event_log::record_interrupts(mask, true);
+}
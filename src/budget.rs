@@ -0,0 +1,44 @@
+/// Checks at compile time that a list of spinlock numbers fits into the hardware's lock budget.
+///
+/// The rp2040 has 32 hardware spinlocks, of which `rp2040_hal` reserves spinlock 31 for its
+/// `critical-section` implementation. Listing every lock number a firmware declares in one place
+/// and checking it with this function catches collisions before they turn into mysterious
+/// contention or deadlocks at runtime.
+///
+/// # Panics
+///
+/// Panics if a number is used twice, if the reserved spinlock 31 is used or if a number is not a
+/// valid spinlock number at all. This also rules out requesting more than 31 distinct locks.
+/// Evaluated in a `const` item, every panic is a compile-time error.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rp_spinlockmutex::check_lock_budget;
+/// const _: () = check_lock_budget(&[0, 1, 7, 12]);
+/// ```
+///
+/// ```compile_fail
+/// use rp_spinlockmutex::check_lock_budget;
+/// const _: () = check_lock_budget(&[0, 1, 7, 1]); // ❌ lock 1 used twice ❌
+/// ```
+///
+/// ```compile_fail
+/// use rp_spinlockmutex::check_lock_budget;
+/// const _: () = check_lock_budget(&[0, 31]); // ❌ lock 31 is reserved ❌
+/// ```
+pub const fn check_lock_budget(locks: &[usize]) {
+    let mut used: u32 = 0;
+    let mut i = 0;
+    while i < locks.len() {
+        let n = locks[i];
+        assert!(n < 32, "spinlock numbers must be in the range 0 to 31");
+        assert!(
+            n != 31,
+            "spinlock 31 is reserved for the critical-section implementation"
+        );
+        assert!(used & (1 << n) == 0, "spinlock numbers must be distinct");
+        used |= 1 << n;
+        i += 1;
+    }
+}
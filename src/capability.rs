@@ -0,0 +1,139 @@
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+
+use cortex_m::interrupt::CriticalSection;
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock like [`lock`](Self::lock), but requires a reference to a
+    /// capability token of type `C`.
+    ///
+    /// The token is never read, it only has to exist: if `C` can only be created by the code
+    /// enabling a subsystem, locking through this method encodes "the subsystem is enabled" in
+    /// the type system, without any runtime cost. The returned guard borrows the token, so the
+    /// token cannot be consumed (e.g. by a `disable(self)` method) while the lock is held.
+    ///
+    /// This does not stop other code from calling [`lock`](Self::lock) directly, so keep the
+    /// mutex private to the module that hands out the capability-gated accessors.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    ///
+    /// /// Proof that the peripheral was enabled.
+    /// pub struct PeripheralEnabled {
+    ///     _private: (),
+    /// }
+    ///
+    /// impl PeripheralEnabled {
+    ///     pub fn enable() -> Self {
+    ///         // power up the peripheral
+    ///         PeripheralEnabled { _private: () }
+    ///     }
+    ///
+    ///     pub fn disable(self) {
+    ///         // power down the peripheral
+    ///     }
+    /// }
+    ///
+    /// static REGISTERS: SpinlockMutex<7, [u32; 4]> = SpinlockMutex::new([0; 4]);
+    ///
+    /// let enabled = PeripheralEnabled::enable();
+    /// REGISTERS.lock_with_cap(&enabled)[0] = 1;
+    /// enabled.disable();
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # use rp_spinlockmutex::SpinlockMutex;
+    /// # pub struct PeripheralEnabled;
+    /// # impl PeripheralEnabled { pub fn disable(self) {} }
+    /// # static REGISTERS: SpinlockMutex<7, [u32; 4]> = SpinlockMutex::new([0; 4]);
+    /// let enabled = PeripheralEnabled;
+    /// let mut registers = REGISTERS.lock_with_cap(&enabled);
+    /// enabled.disable(); // ❌ the token is borrowed by the guard ❌
+    /// registers[0] = 1;
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lock_with_cap<'a, C: ?Sized>(&'a self, _cap: &'a C) -> CapabilityGuard<'a, N, T, C> {
+        CapabilityGuard::new(self.lock())
+    }
+
+    /// Acquires the mutex lock inside a `cortex_m::interrupt::free` section, whose token `cs`
+    /// proves that interrupts are disabled on the current core.
+    ///
+    /// Disabling interrupts protects against the handlers of the current core, and the
+    /// spinlock against the other core, which together make the data safe to share across
+    /// interrupt handlers of both cores. The guard borrows `cs`, so it cannot outlive the
+    /// section and be used after interrupts are enabled again.
+    ///
+    /// Unlike the `critical-section` implementation of `rp2040_hal`, this does not claim
+    /// spinlock 31, so it does not serialize against unrelated critical sections of the other
+    /// core.
+    ///
+    /// ```no_run
+    /// use cortex_m::interrupt;
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static EVENTS: SpinlockMutex<7, u32> = SpinlockMutex::new(0);
+    ///
+    /// // In the main loop of either core, and in interrupt handlers on both
+    /// interrupt::free(|cs| *EVENTS.lock_in_free(cs) += 1);
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lock_in_free<'cs>(&'cs self, cs: &'cs CriticalSection) -> CapabilityGuard<'cs, N, T, CriticalSection> {
+        self.lock_with_cap(cs)
+    }
+}
+
+/// A guard obtained from [`SpinlockMutex::lock_with_cap`] or [`SpinlockMutex::lock_in_free`].
+///
+/// It behaves exactly like a [`SpinlockMutexGuard`], but borrows the capability token
+/// for as long as the lock is held.
+#[must_use = "if unused the SpinlockMutex will immediately unlock"]
+pub struct CapabilityGuard<'a, const N: usize, T: ?Sized, C: ?Sized>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    guard: SpinlockMutexGuard<N, T>,
+    _cap: PhantomData<&'a C>,
+}
+
+impl<const N: usize, T: ?Sized, C: ?Sized> CapabilityGuard<'_, N, T, C>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    pub(crate) fn new(guard: SpinlockMutexGuard<N, T>) -> Self {
+        CapabilityGuard {
+            guard,
+            _cap: PhantomData,
+        }
+    }
+}
+
+impl<const N: usize, T: ?Sized, C: ?Sized> Deref for CapabilityGuard<'_, N, T, C>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<const N: usize, T: ?Sized, C: ?Sized> DerefMut for CapabilityGuard<'_, N, T, C>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
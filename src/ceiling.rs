@@ -0,0 +1,99 @@
+use core::ops::{Deref, DerefMut};
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::masking::Unmask;
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+/// Returns the mask of the enabled interrupts whose priority is `ceiling` or lower.
+fn interrupts_below(ceiling: u8) -> u32 {
+    let enabled = crate::backend::enabled_interrupts();
+    (0..32).fold(0, |mask, irq| {
+        if enabled & 1 << irq != 0 && crate::backend::interrupt_priority(irq) >= ceiling {
+            mask | 1 << irq
+        } else {
+            mask
+        }
+    })
+}
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock with all interrupts of priority `ceiling` or lower disabled on
+    /// the current core, emulating the priority ceiling protocol.
+    ///
+    /// `ceiling` uses the NVIC encoding, where lower values are more urgent: all enabled
+    /// interrupts whose priority value is greater than or equal to `ceiling` are disabled. Set
+    /// it to the most urgent priority of all handlers locking this mutex. Then none of them can
+    /// preempt the critical section, so a handler never waits on a lock its own core holds, and
+    /// handlers of medium priority cannot delay the critical section either. More urgent
+    /// interrupts stay responsive.
+    ///
+    /// The Cortex-M0+ has no `BASEPRI` register, so the interrupts are disabled one by one in
+    /// the NVIC like with [`lock_masking`](Self::lock_masking). This happens *before* the
+    /// spinlock is claimed, and they are re-enabled *after* the spinlock was released on drop.
+    /// Interrupts enabled during the critical section, or already disabled before it, are not
+    /// touched on drop, so nested calls restore the state of their caller. If locking panics,
+    /// the interrupts are re-enabled while unwinding.
+    ///
+    /// The NVIC is core-local: the handlers may still run on the other core, where they simply
+    /// wait for the lock as usual. On the host, the interrupts are disabled in the
+    /// `HostNvic` emulation instead.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static SAMPLES: SpinlockMutex<7, [u16; 64]> = SpinlockMutex::new([0; 64]);
+    ///
+    /// // Shared with handlers of priority 0x40 and 0x80
+    /// let mut samples = SAMPLES.lock_ceiling(0x40);
+    /// samples.fill(0);
+    /// ```
+    #[inline]
+    pub fn lock_ceiling(&self, ceiling: u8) -> CeilingGuard<N, T> {
+        // Created first, so that the interrupts are also restored if `lock` panics.
+        let unmask = Unmask::new(interrupts_below(ceiling));
+        CeilingGuard {
+            guard: self.lock(),
+            _unmask: unmask,
+        }
+    }
+}
+
+/// A guard obtained from [`SpinlockMutex::lock_ceiling`].
+///
+/// It behaves like a [`SpinlockMutexGuard`] and additionally keeps the interrupts below the
+/// ceiling disabled, which are re-enabled right after the spinlock is released on drop.
+#[must_use = "if unused the SpinlockMutex will immediately unlock"]
+pub struct CeilingGuard<const N: usize, T: ?Sized>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    // Like for the `MaskingGuard`, the spinlock is released first, as fields are dropped in
+    // order of declaration.
+    guard: SpinlockMutexGuard<N, T>,
+    _unmask: Unmask,
+}
+
+impl<const N: usize, T: ?Sized> Deref for CeilingGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<const N: usize, T: ?Sized> DerefMut for CeilingGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
@@ -0,0 +1,94 @@
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::SpinlockMutex;
+
+/// A [`Cell`](core::cell::Cell)-like container for `Copy` values, protected by the rp2040 hardware spinlock.
+///
+/// Every operation claims spinlock `N` for exactly as long as it takes to read or write the value,
+/// so there is no guard that could be held for too long by accident.
+///
+/// The same rules as for [`SpinlockMutex`] apply: all users of spinlock `N` contend for the same
+/// hardware lock, and interrupts are not deactivated while it is claimed.
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::SpinlockCell;
+/// static COUNTER: SpinlockCell<7, u32> = SpinlockCell::new(0);
+/// # fn run_on_core1(f: impl FnOnce()) {}
+///
+/// run_on_core1(|| {
+///     for _ in 0..10 {
+///         COUNTER.update(|n| n + 1);
+///     }
+/// });
+///
+/// for _ in 0..10 {
+///     COUNTER.update(|n| n + 1);
+/// }
+///
+/// assert_eq!(COUNTER.get(), 20);
+/// ```
+pub struct SpinlockCell<const N: usize, T: Copy>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    mutex: SpinlockMutex<N, T>,
+}
+
+impl<const N: usize, T: Copy> SpinlockCell<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates a new hardware based spinlock cell containing the given value.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockCell;
+    /// let cell: SpinlockCell<7, i32> = SpinlockCell::new(42);
+    /// ```
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self {
+            mutex: SpinlockMutex::new(value),
+        }
+    }
+
+    /// Returns a copy of the contained value.
+    #[inline]
+    pub fn get(&self) -> T {
+        *self.mutex.lock()
+    }
+
+    /// Sets the contained value.
+    #[inline]
+    pub fn set(&self, value: T) {
+        *self.mutex.lock() = value;
+    }
+
+    /// Replaces the contained value with `value` and returns the old contained value.
+    #[inline]
+    pub fn replace(&self, value: T) -> T {
+        core::mem::replace(&mut *self.mutex.lock(), value)
+    }
+
+    /// Updates the contained value using a function.
+    ///
+    /// The read, the call to `f` and the write all happen while the spinlock is claimed, so no
+    /// update from the other core can get lost in between. Keep `f` short, as the other core
+    /// spins for as long as it runs.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockCell;
+    /// let cell: SpinlockCell<7, i32> = SpinlockCell::new(1);
+    /// cell.update(|n| n * 2);
+    ///
+    /// assert_eq!(cell.get(), 2);
+    /// ```
+    #[inline]
+    pub fn update(&self, f: impl FnOnce(T) -> T) {
+        let mut guard = self.mutex.lock();
+        *guard = f(*guard);
+    }
+}
@@ -0,0 +1,69 @@
+#[cfg(target_arch = "arm")]
+use rp2040_hal::pac;
+
+/// A monotonic clock, used by the timing features to measure how long a lock is held.
+///
+/// The timing features default to the rp2040's [`SystemTimer`], as the Cortex-M0+ has no DWT
+/// cycle counter. Their `_with` variants take any clock instead, e.g. a clock that is advanced
+/// manually, which makes code using them testable on the host.
+///
+/// The unit of the ticks is up to the implementation, and budgets and thresholds are given in
+/// the same unit.
+///
+/// # Example
+///
+/// ```no_run
+/// use core::sync::atomic::{AtomicU64, Ordering};
+/// use rp_spinlockmutex::MonotonicCycles;
+///
+/// /// A clock for tests, advanced by hand.
+/// struct TestClock(AtomicU64);
+///
+/// impl TestClock {
+///     fn advance(&self, ticks: u64) {
+///         self.0.fetch_add(ticks, Ordering::Relaxed);
+///     }
+/// }
+///
+/// impl MonotonicCycles for TestClock {
+///     fn now(&self) -> u64 {
+///         self.0.load(Ordering::Relaxed)
+///     }
+/// }
+/// ```
+pub trait MonotonicCycles {
+    /// Returns the current time in ticks. It must never decrease.
+    fn now(&self) -> u64;
+}
+
+impl<C: ?Sized + MonotonicCycles> MonotonicCycles for &C {
+    #[inline]
+    fn now(&self) -> u64 {
+        (**self).now()
+    }
+}
+
+/// The rp2040's 64 bit system timer, counting microseconds.
+///
+/// The timer has to be running, which e.g. `rp2040_hal::clocks::init_clocks_and_plls` takes
+/// care of.
+#[cfg(target_arch = "arm")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemTimer;
+
+#[cfg(target_arch = "arm")]
+impl MonotonicCycles for SystemTimer {
+    #[inline]
+    fn now(&self) -> u64 {
+        // SAFETY: We're only reading from these registers
+        let timer = unsafe { &*pac::TIMER::ptr() };
+        // The raw registers are not latched, so retry if the upper half changed in between.
+        loop {
+            let high = timer.timerawh.read().bits();
+            let low = timer.timerawl.read().bits();
+            if timer.timerawh.read().bits() == high {
+                break u64::from(high) << 32 | u64::from(low);
+            }
+        }
+    }
+}
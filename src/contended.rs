@@ -0,0 +1,43 @@
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{abort, CoreId, SpinlockMutex, SpinlockMutexGuard};
+
+/// The error returned by [`SpinlockMutex::try_lock_diag`] if the spinlock is held elsewhere.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ContendedBy {
+    /// The core recorded as the holder of the spinlock, if known.
+    ///
+    /// The hardware does not record which core claimed a spinlock, so this is only known if the
+    /// lock was acquired with [`SpinlockMutex::lock_abort_safe`], and `None` otherwise.
+    pub core: Option<CoreId>,
+}
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Attempts to acquire the mutex lock like [`try_lock`](Self::try_lock), but reports the core
+    /// likely holding the lock on failure.
+    ///
+    /// The reported core is best-effort only: the owner is read after the claim failed, so the
+    /// lock may have been released, or even re-acquired by another owner, in between. Use it to
+    /// spot contention patterns, not for making decisions.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::{CoreId, SpinlockMutex};
+    /// static MUTEX: SpinlockMutex<7, i32> = SpinlockMutex::new(0);
+    ///
+    /// match MUTEX.try_lock_diag() {
+    ///     Ok(mut guard) => *guard += 1,
+    ///     Err(contended) if contended.core == Some(CoreId::current()) => {
+    ///         // held by this core, e.g. by the code this interrupt preempted
+    ///     }
+    ///     Err(_) => {}
+    /// }
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn try_lock_diag(&self) -> Result<SpinlockMutexGuard<N, T>, ContendedBy> {
+        self.try_lock().ok_or_else(|| ContendedBy { core: abort::owner(N) })
+    }
+}
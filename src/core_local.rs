@@ -0,0 +1,172 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::SpinlockMutex;
+
+/// Per-core data: one copy of `T` for each core, where each core accesses its own copy without
+/// claiming a spinlock.
+///
+/// [`with_local`](Self::with_local) accesses the copy of the current core, and
+/// [`with_other`](Self::with_other) the copy of the other core. The former is the common case
+/// and stays off the spinlock, the latter is meant for occasional cross-core inspection, e.g.
+/// collecting per-core statistics.
+///
+/// # Soundness
+///
+/// A copy is only ever accessed by its own core, except during `with_other` of the other core.
+/// Not claiming any lock is therefore not enough: the local access has to exclude a concurrent
+/// visit of the other core. Each copy has two flags for that, which only need atomic loads and
+/// stores and therefore work on the Cortex-M0+:
+///
+/// - its own core sets `busy` for the duration of `with_local`, and
+/// - the visiting core sets `visiting` for the duration of `with_other`.
+///
+/// Each side first sets its own flag and then checks the flag of the other side, with
+/// sequentially consistent orderings, so at least one of them sees the other's flag. The local
+/// access backs off until the visit is over, and the visit waits until the local access is
+/// over, so they never overlap. Visits claim spinlock `N` to exclude each other, e.g. visits
+/// from an interrupt handler and the code it preempted.
+///
+/// An interrupt handler calling `with_local` while the code it preempted is inside
+/// `with_local` would alias the copy. This is detected, and panics.
+///
+/// # Deadlock
+///
+/// `f` must not call `with_other` inside `with_local`: if both cores do that at the same
+/// time, each waits for the other's local access to end. As for [`SpinlockMutex::lock`],
+/// `with_other` deadlocks if an interrupt handler calls it while preempting `with_other`.
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::CoreLocal;
+/// static PACKETS: CoreLocal<7, u32> = CoreLocal::new(0, 0);
+///
+/// // On both cores, for every packet
+/// PACKETS.with_local(|packets| *packets += 1);
+///
+/// // Occasionally, on core 0
+/// let total = PACKETS.with_local(|packets| *packets) + PACKETS.with_other(|packets| *packets);
+/// ```
+pub struct CoreLocal<const N: usize, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Set by each core while it accesses its own copy.
+    busy: [AtomicBool; 2],
+    /// Set by the other core while it accesses the respective copy.
+    visiting: [AtomicBool; 2],
+    /// Excludes concurrent visits.
+    visits: SpinlockMutex<N, ()>,
+    copies: [UnsafeCell<T>; 2],
+}
+
+unsafe impl<const N: usize, T: Send> Sync for CoreLocal<N, T> where Spinlock<N>: SpinlockValid {}
+
+impl<const N: usize, T> CoreLocal<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates per-core data with the initial copies of core 0 and core 1.
+    #[inline]
+    pub const fn new(core0: T, core1: T) -> Self {
+        Self {
+            busy: [AtomicBool::new(false), AtomicBool::new(false)],
+            visiting: [AtomicBool::new(false), AtomicBool::new(false)],
+            visits: SpinlockMutex::new(()),
+            copies: [UnsafeCell::new(core0), UnsafeCell::new(core1)],
+        }
+    }
+
+    /// Runs `f` on the copy of the current core, without claiming the spinlock.
+    ///
+    /// Only waits while the other core is inside [`with_other`](Self::with_other).
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from an interrupt handler while the code it preempted is inside
+    /// `with_local`.
+    #[inline]
+    #[track_caller]
+    pub fn with_local<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let core = crate::backend::core() as usize;
+        let (busy, visiting) = (&self.busy[core], &self.visiting[core]);
+        if Self::enter(busy) {
+            panic!(
+                "CoreLocal<{}, _>{} accessed reentrantly on core {}",
+                N,
+                crate::diag::NameSuffix(N),
+                core
+            );
+        }
+        // Pairs with `with_other`, see the soundness section above.
+        while visiting.load(Ordering::SeqCst) {
+            busy.store(false, Ordering::Relaxed);
+            while visiting.load(Ordering::Relaxed) {
+                core::hint::spin_loop();
+            }
+            busy.store(true, Ordering::SeqCst);
+        }
+        let _busy = ClearOnDrop(busy);
+        // SAFETY: `busy` is set and `visiting` is not, so the other core does not access this
+        //         copy until `_busy` clears `busy`. Reentrant calls on this core panic.
+        f(unsafe { &mut *self.copies[core].get() })
+    }
+
+    /// Locks spinlock `N` and runs `f` on the copy of the other core.
+    ///
+    /// Waits until the other core is not inside [`with_local`](Self::with_local).
+    #[inline]
+    #[track_caller]
+    pub fn with_other<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let other = 1 - crate::backend::core() as usize;
+        let (busy, visiting) = (&self.busy[other], &self.visiting[other]);
+        let _visit = self.visits.lock();
+        // Pairs with `with_local`, see the soundness section above.
+        visiting.store(true, Ordering::SeqCst);
+        let _visiting = ClearOnDrop(visiting);
+        while busy.load(Ordering::SeqCst) {
+            core::hint::spin_loop();
+        }
+        // SAFETY: `visiting` is set and `busy` is not, so the other core does not access its
+        //         copy until `_visiting` clears `visiting`. Other visits are excluded by
+        //         `_visit`, which is dropped after `_visiting`.
+        f(unsafe { &mut *self.copies[other].get() })
+    }
+
+    /// Returns the number of the hardware spinlock used for visits of the other core.
+    #[inline]
+    pub const fn spinlock_number(&self) -> usize {
+        N
+    }
+
+    /// Sets `busy`, returning whether it was set already.
+    #[inline(always)]
+    fn enter(busy: &AtomicBool) -> bool {
+        // The flag is only written by this core. An interrupt handler preempting between the
+        // load and the store leaves it as it found it, or panics.
+        #[cfg(target_arch = "arm")]
+        {
+            let entered = busy.load(Ordering::Relaxed);
+            busy.store(true, Ordering::SeqCst);
+            entered
+        }
+        // Threads of the host emulate the same core unless told otherwise, so the flag is set
+        // atomically there to turn concurrent calls into a panic as well.
+        #[cfg(not(target_arch = "arm"))]
+        busy.swap(true, Ordering::SeqCst)
+    }
+}
+
+/// Clears a flag on drop, also if `f` panics, making the accesses of `f` visible to whoever
+/// sees it cleared.
+struct ClearOnDrop<'a>(&'a AtomicBool);
+
+impl Drop for ClearOnDrop<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
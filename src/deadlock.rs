@@ -0,0 +1,55 @@
+use rp2040_hal::pac::SCB;
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::spin::BoundedRetries;
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+/// What [`SpinlockMutex::lock_or_reset`] does if the spin budget is exhausted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeadlockAction {
+    /// Request a system reset through `SCB::sys_reset`.
+    Reset,
+    /// Panic, e.g. to halt in the debugger during development.
+    Panic,
+}
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock, assuming a deadlock if the spinlock could not be claimed within
+    /// `spin_budget` attempts, in which case `action` is taken.
+    ///
+    /// This is a last-resort recovery for systems that must not hang but have no watchdog (see
+    /// `lock_watchdog_feeding` of the `embedded-hal` feature otherwise). Resetting loses all
+    /// state of both cores, so it should only ever trigger on an actual deadlock.
+    ///
+    /// Choose `spin_budget` well above the longest legitimate wait: a single attempt takes a few
+    /// cycles, so e.g. at 125 MHz, a budget of `10_000_000` allows waiting for roughly a quarter
+    /// of a second. Under high contention, a budget close to the longest critical section leads
+    /// to false-positive resets.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::{DeadlockAction, SpinlockMutex};
+    /// static MUTEX: SpinlockMutex<7, i32> = SpinlockMutex::new(0);
+    ///
+    /// let action = if cfg!(debug_assertions) { DeadlockAction::Panic } else { DeadlockAction::Reset };
+    /// *MUTEX.lock_or_reset(10_000_000, action) += 1;
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lock_or_reset(&self, spin_budget: u32, action: DeadlockAction) -> SpinlockMutexGuard<N, T> {
+        match self.lock_with_strategy(&mut BoundedRetries(spin_budget)) {
+            Some(guard) => guard,
+            None => match action {
+                DeadlockAction::Reset => SCB::sys_reset(),
+                DeadlockAction::Panic => panic!(
+                    "SpinlockMutex<{}, _>{} could not be locked within {} attempts",
+                    N,
+                    crate::diag::NameSuffix(N),
+                    spin_budget
+                ),
+            },
+        }
+    }
+}
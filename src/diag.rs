@@ -0,0 +1,315 @@
+//! Diagnostics for inspecting the state of the hardware spinlocks.
+//!
+//! Spinlocks can be claimed or released by either core at any time, so all values
+//! returned here are snapshots and only meant for debugging and logging.
+
+use core::fmt;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// The table installed with [`set_lock_names`], or null.
+static LOCK_NAMES: AtomicPtr<[&'static str; 32]> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Installs a table naming the spinlocks, used by all diagnostics of this crate.
+///
+/// Entry `n` names spinlock `n`. Held spinlocks are listed with their names by
+/// [`dump_held_locks`], and the panics of this crate (e.g. of `core-pinning` or
+/// `lock_or_reset`) include the name of the spinlock involved. Empty entries are treated as
+/// unnamed. Installing a table replaces any previously installed one.
+///
+/// ```no_run
+/// use rp_spinlockmutex::diag::{lock_name, set_lock_names};
+///
+/// static LOCK_NAMES: [&str; 32] = {
+///     let mut names = [""; 32];
+///     names[7] = "sensor_state";
+///     names[31] = "critical_section";
+///     names
+/// };
+///
+/// set_lock_names(&LOCK_NAMES);
+///
+/// assert_eq!(lock_name(7), Some("sensor_state"));
+/// ```
+#[inline]
+pub fn set_lock_names(names: &'static [&'static str; 32]) {
+    LOCK_NAMES.store(names as *const _ as *mut _, Ordering::Release);
+}
+
+/// Returns the name of spinlock `n` from the table installed with [`set_lock_names`], or
+/// `None` if no table is installed, the entry is empty or `n` is out of range.
+#[inline]
+pub fn lock_name(n: usize) -> Option<&'static str> {
+    // SAFETY: Non-null values are only ever stored by `set_lock_names`, from a `'static` reference.
+    let names = unsafe { LOCK_NAMES.load(Ordering::Acquire).as_ref() }?;
+    names.get(n).copied().filter(|name| !name.is_empty())
+}
+
+/// Formats as ` ('name')` if spinlock `.0` is named, and as nothing otherwise.
+pub(crate) struct NameSuffix(pub(crate) usize);
+
+impl fmt::Display for NameSuffix {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match lock_name(self.0) {
+            Some(name) => write!(f, " ('{}')", name),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the raw `SPINLOCK_ST` register, where bit `n` is set if spinlock `n` is claimed.
+#[inline]
+pub fn locked_mask() -> u32 {
+    crate::backend::locked_mask()
+}
+
+/// Returns an iterator over the numbers of all currently claimed spinlocks, in ascending order.
+///
+/// ```no_run
+/// use rp_spinlockmutex::diag::claimed_locks;
+///
+/// let _lock = rp2040_hal::sio::Spinlock::<7>::claim();
+///
+/// assert!(claimed_locks().any(|n| n == 7));
+/// ```
+#[inline]
+pub fn claimed_locks() -> impl Iterator<Item = usize> {
+    SetBits(locked_mask())
+}
+
+/// Writes a human-readable list of the currently claimed spinlocks to `w`, e.g.
+/// `held spinlocks: 7, 31` or `held spinlocks: none`.
+///
+/// Spinlocks named with [`set_lock_names`] are listed with their names, e.g.
+/// `held spinlocks: 7 ('sensor_state'), 31`.
+///
+/// This neither allocates nor takes any lock, so it can be called from a `#[panic_handler]`
+/// to see which spinlocks were held when the firmware panicked. Spinlocks held by the other
+/// core show up as well, as the hardware does not record which core claimed a lock.
+///
+/// ```no_run
+/// use core::fmt::Write;
+/// use rp_spinlockmutex::diag::dump_held_locks;
+///
+/// // registered with #[panic_handler]
+/// fn panic(info: &core::panic::PanicInfo, uart: &mut impl Write) -> ! {
+///     let _ = writeln!(uart, "{}", info);
+///     let _ = dump_held_locks(uart);
+///     loop {}
+/// }
+/// ```
+pub fn dump_held_locks(w: &mut impl core::fmt::Write) -> core::fmt::Result {
+    w.write_str("held spinlocks: ")?;
+    let mut locks = claimed_locks();
+    match locks.next() {
+        Some(first) => {
+            write!(w, "{}{}", first, NameSuffix(first))?;
+            for n in locks {
+                write!(w, ", {}{}", n, NameSuffix(n))?;
+            }
+        }
+        None => w.write_str("none")?,
+    }
+    Ok(())
+}
+
+/// Iterates the indices of the set bits of a mask, lowest first.
+struct SetBits(u32);
+
+impl Iterator for SetBits {
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        if self.0 == 0 {
+            return None;
+        }
+        let n = self.0.trailing_zeros() as usize;
+        // Clear the lowest set bit
+        self.0 &= self.0 - 1;
+        Some(n)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+/// Lock statistics of one spinlock since boot, see [`lock_stats`].
+#[cfg(feature = "metrics")]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LockStats {
+    /// The number of times the spinlock was acquired through a [`SpinlockMutex`](crate::SpinlockMutex).
+    pub acquisitions: u32,
+    /// The number of [`lock`](crate::SpinlockMutex::lock) calls that had to wait for the spinlock,
+    /// including retrying ones such as [`lock_or_abort`](crate::SpinlockMutex::lock_or_abort)
+    /// that eventually acquired it.
+    pub contentions: u32,
+    /// The most failed attempts to claim the spinlock a single `lock` call needed.
+    pub max_spins: u32,
+}
+
+/// Returns the statistics of spinlock `n` since boot.
+///
+/// The counters are updated while the spinlock is held, and only by mutexes of this crate.
+/// Failed [`try_lock`](crate::SpinlockMutex::try_lock) calls are not counted as contention.
+///
+/// # Panics
+///
+/// Panics if `n` is not in the range 0 to 31.
+///
+/// ```no_run
+/// use rp_spinlockmutex::diag::lock_stats;
+///
+/// let stats = lock_stats(7);
+/// assert!(stats.contentions <= stats.acquisitions);
+/// ```
+#[cfg(feature = "metrics")]
+#[inline]
+pub fn lock_stats(n: usize) -> LockStats {
+    crate::metrics::stats(n)
+}
+
+/// Zeroes the statistics of all spinlocks, e.g. to ignore the contention while booting.
+///
+/// Each spinlock is claimed in turn while its counters are zeroed, so concurrent updates on
+/// the other core are never corrupted. See [`SpinlockMutex::reset_stats`](crate::SpinlockMutex::reset_stats)
+/// to only reset a single spinlock.
+///
+/// # Deadlock
+///
+/// Calling this while the current core holds any spinlock, including from within a
+/// `critical_section` (which holds spinlock 31), will cause a deadlock.
+///
+/// ```no_run
+/// use rp_spinlockmutex::diag::{lock_stats, reset_stats};
+///
+/// // after initialization
+/// reset_stats();
+///
+/// assert_eq!(lock_stats(7).acquisitions, 0);
+/// ```
+#[cfg(feature = "metrics")]
+#[inline]
+pub fn reset_stats() {
+    crate::metrics::reset_all()
+}
+
+/// Writes a table of the statistics of all spinlocks acquired since boot to `w`.
+///
+/// ```text
+/// lock  acquisitions  contentions   max spins
+///    7         12345           12         340
+/// ```
+///
+/// With `defmt`, which cannot write into a [`Write`](core::fmt::Write), use
+/// `log_lock_summary` instead.
+#[cfg(feature = "metrics")]
+pub fn print_lock_summary(w: &mut impl core::fmt::Write) -> core::fmt::Result {
+    writeln!(w, "lock  acquisitions  contentions   max spins")?;
+    for n in 0..32 {
+        let stats = lock_stats(n);
+        if stats.acquisitions != 0 {
+            writeln!(
+                w,
+                "{:>4}  {:>12}  {:>11}  {:>10}",
+                n, stats.acquisitions, stats.contentions, stats.max_spins
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Logs the statistics of all spinlocks acquired since boot with `defmt`, the counterpart of
+/// [`print_lock_summary`]. Each spinlock is logged as one line:
+///
+/// ```text
+/// spinlock 7: 12345 acquisitions, 12 contentions, at most 340 spins
+/// ```
+///
+/// ```no_run
+/// // e.g. after a stress test
+/// rp_spinlockmutex::diag::log_lock_summary();
+/// ```
+#[cfg(all(feature = "metrics", feature = "defmt", target_arch = "arm"))]
+pub fn log_lock_summary() {
+    for n in 0..32 {
+        let stats = lock_stats(n);
+        if stats.acquisitions != 0 {
+            defmt::info!(
+                "spinlock {=usize}: {=u32} acquisitions, {=u32} contentions, at most {=u32} spins",
+                n,
+                stats.acquisitions,
+                stats.contentions,
+                stats.max_spins
+            );
+        }
+    }
+}
+
+/// An event recorded by the `test-trace` event log, see [`drain_events`].
+#[cfg(all(feature = "test-trace", not(target_arch = "arm")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TraceEvent {
+    /// The core the event happened on, as emulated by the current thread.
+    pub core: u8,
+    /// What happened.
+    pub kind: TraceKind,
+    /// The position of the event in the global order of all recorded events, counting from 0.
+    ///
+    /// A release of a spinlock always gets an earlier timestamp than the acquisition it
+    /// enables.
+    pub timestamp: u64,
+}
+
+/// What a [`TraceEvent`] records.
+#[cfg(all(feature = "test-trace", not(target_arch = "arm")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TraceKind {
+    /// The spinlock of the given number was acquired.
+    Acquired(usize),
+    /// The spinlock of the given number is about to be released.
+    Released(usize),
+    /// The interrupt of the given number was disabled in the [`HostNvic`](crate::HostNvic).
+    Masked(u16),
+    /// The interrupt of the given number was enabled in the [`HostNvic`](crate::HostNvic).
+    Unmasked(u16),
+}
+
+/// Takes the events the `test-trace` event log recorded since the last call, in the order of
+/// their timestamps.
+///
+/// Every [`SpinlockMutex`](crate::SpinlockMutex) acquisition and release reported to the
+/// trace hook is recorded here, on all locks and threads, and so is every change of the
+/// [`HostNvic`](crate::HostNvic), e.g. by [`lock_masking`](crate::SpinlockMutex::lock_masking).
+/// The log keeps the last 256 events in a fixed ring, older ones are lost. Recording only uses
+/// atomics, so it never locks or allocates, but it adds a shared counter to every lock and
+/// release. The log is therefore meant for host tests asserting ordering properties, not for
+/// production.
+///
+/// As the log is global, tests running in parallel see each other's events, and should
+/// filter by lock number and only drain from a single test.
+///
+/// An event is given its timestamp before it is written into the log. If another thread is
+/// between the two while draining, its event is skipped and lost for later calls as well, while
+/// newer events are still returned. Drain once the threads of the test are done recording,
+/// e.g. after joining them, to see all of their events.
+///
+/// ```
+/// use rp_spinlockmutex::diag::{drain_events, TraceKind};
+/// use rp_spinlockmutex::SpinlockMutex;
+/// static MUTEX: SpinlockMutex<7, u32> = SpinlockMutex::new(0);
+///
+/// *MUTEX.lock() += 1;
+///
+/// let events: Vec<_> = drain_events().map(|e| e.kind).collect();
+/// assert_eq!(events, [TraceKind::Acquired(7), TraceKind::Released(7)]);
+/// ```
+#[cfg(all(feature = "test-trace", not(target_arch = "arm")))]
+#[inline]
+pub fn drain_events() -> impl Iterator<Item = TraceEvent> {
+    crate::event_log::drain()
+}
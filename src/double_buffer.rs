@@ -0,0 +1,188 @@
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+/// Two buffers for ping-pong operation, each protected by its own hardware spinlock.
+///
+/// One side (e.g. an interrupt handler collecting samples) fills one buffer through
+/// [`fill_buffer`](Self::fill_buffer), while the other side (e.g. the main loop on the other
+/// core) processes the other buffer through [`drain_buffer`](Self::drain_buffer). Once a buffer
+/// is complete, [`swap`](Self::swap) exchanges the roles of both buffers.
+///
+/// The buffers use the distinct spinlocks `A` and `B`, so filling and draining never contend
+/// with each other, unless both sides happen to work on the same buffer around a swap.
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::DoubleBuffer;
+/// static SAMPLES: DoubleBuffer<3, 4, [u16; 64]> = DoubleBuffer::new([0; 64], [0; 64]);
+///
+/// // In the ADC interrupt handler
+/// fn on_conversion_done(index: usize, sample: u16) {
+///     SAMPLES.fill_buffer()[index] = sample;
+///     if index == 63 {
+///         SAMPLES.swap();
+///     }
+/// }
+///
+/// // In the main loop on core 1
+/// let sum: u32 = SAMPLES.drain_buffer().iter().map(|&s| u32::from(s)).sum();
+/// ```
+///
+/// # DMA
+///
+/// A DMA transfer may only write into a buffer while its guard is held. Here, core 0 lets
+/// DMA channel 0 fill a buffer with ADC samples, and swaps once the transfer is complete,
+/// while core 1 processes the other buffer. If core 1 is slower than the ADC, core 0 waits
+/// for it in `fill_buffer`, instead of overwriting samples not processed yet.
+///
+/// ```no_run
+/// use rp2040_hal::pac;
+/// use rp_spinlockmutex::DoubleBuffer;
+/// static SAMPLES: DoubleBuffer<3, 4, [u16; 64]> = DoubleBuffer::new([0; 64], [0; 64]);
+/// # fn process(_: &[u16; 64]) {}
+///
+/// // On core 0, with the ADC in free-running mode and its FIFO's DREQ enabled
+/// # fn capture() {
+/// let (dma, adc) = unsafe { (&*pac::DMA::ptr(), &*pac::ADC::ptr()) };
+/// loop {
+///     let mut buffer = SAMPLES.fill_buffer();
+///     let channel = &dma.ch[0];
+///     // SAFETY: The guard is held until the transfer into the buffer is complete.
+///     unsafe {
+///         channel.ch_read_addr.write(|w| w.bits(&adc.fifo as *const _ as u32));
+///         channel.ch_write_addr.write(|w| w.bits(buffer.as_mut_ptr() as u32));
+///         channel.ch_trans_count.write(|w| w.bits(64));
+///         channel.ch_ctrl_trig.write(|w| {
+///             w.data_size().size_halfword();
+///             w.incr_write().set_bit();
+///             w.treq_sel().adc();
+///             w.chain_to().bits(0);
+///             w.en().set_bit()
+///         });
+///     }
+///     while channel.ch_ctrl_trig.read().busy().bit_is_set() {}
+///     drop(buffer);
+///     SAMPLES.swap();
+/// }
+/// # }
+///
+/// // On core 1
+/// # fn drain() {
+/// loop {
+///     process(&SAMPLES.drain_buffer());
+/// }
+/// # }
+/// ```
+pub struct DoubleBuffer<const A: usize, const B: usize, T>
+where
+    Spinlock<A>: SpinlockValid,
+    Spinlock<B>: SpinlockValid,
+{
+    a: SpinlockMutex<A, T>,
+    b: SpinlockMutex<B, T>,
+    /// `false` if `a` is the buffer being filled, `true` if it is `b`.
+    filling_b: AtomicBool,
+}
+
+impl<const A: usize, const B: usize, T> DoubleBuffer<A, B, T>
+where
+    Spinlock<A>: SpinlockValid,
+    Spinlock<B>: SpinlockValid,
+{
+    /// Creates a new double buffer. Initially, `a` is filled and `b` is drained.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `A` and `B` are the same spinlock. In a `const` or `static`
+    /// initializer this is a compile-time error.
+    #[inline]
+    pub const fn new(a: T, b: T) -> Self {
+        assert!(A != B, "the buffers of a DoubleBuffer must use distinct spinlocks");
+        Self {
+            a: SpinlockMutex::new(a),
+            b: SpinlockMutex::new(b),
+            filling_b: AtomicBool::new(false),
+        }
+    }
+
+    /// Locks the buffer currently being filled.
+    #[inline]
+    pub fn fill_buffer(&self) -> DoubleBufferGuard<A, B, T> {
+        self.lock_buffer(self.filling_b.load(Ordering::Acquire))
+    }
+
+    /// Locks the buffer currently being drained.
+    #[inline]
+    pub fn drain_buffer(&self) -> DoubleBufferGuard<A, B, T> {
+        self.lock_buffer(!self.filling_b.load(Ordering::Acquire))
+    }
+
+    /// Exchanges the roles of the two buffers.
+    ///
+    /// Guards obtained before the swap stay valid and keep referring to the same buffer.
+    /// As the rp2040 has no atomic read-modify-write instructions, the swap itself is a
+    /// separate load and store: only one side (usually the one filling) may call it.
+    #[inline]
+    pub fn swap(&self) {
+        let filling_b = self.filling_b.load(Ordering::Acquire);
+        self.filling_b.store(!filling_b, Ordering::Release);
+    }
+
+    #[inline]
+    fn lock_buffer(&self, b: bool) -> DoubleBufferGuard<A, B, T> {
+        if b {
+            DoubleBufferGuard::B(self.b.lock())
+        } else {
+            DoubleBufferGuard::A(self.a.lock())
+        }
+    }
+}
+
+/// A guard giving access to one of the buffers of a [`DoubleBuffer`].
+/// If this guard is dropped, the buffer will be unlocked automatically.
+#[must_use = "if unused the buffer will immediately unlock"]
+pub enum DoubleBufferGuard<const A: usize, const B: usize, T>
+where
+    Spinlock<A>: SpinlockValid,
+    Spinlock<B>: SpinlockValid,
+{
+    /// The buffer protected by spinlock `A`.
+    A(SpinlockMutexGuard<A, T>),
+    /// The buffer protected by spinlock `B`.
+    B(SpinlockMutexGuard<B, T>),
+}
+
+impl<const A: usize, const B: usize, T> Deref for DoubleBufferGuard<A, B, T>
+where
+    Spinlock<A>: SpinlockValid,
+    Spinlock<B>: SpinlockValid,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        match self {
+            DoubleBufferGuard::A(guard) => guard,
+            DoubleBufferGuard::B(guard) => guard,
+        }
+    }
+}
+
+impl<const A: usize, const B: usize, T> DerefMut for DoubleBufferGuard<A, B, T>
+where
+    Spinlock<A>: SpinlockValid,
+    Spinlock<B>: SpinlockValid,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        match self {
+            DoubleBufferGuard::A(guard) => guard,
+            DoubleBufferGuard::B(guard) => guard,
+        }
+    }
+}
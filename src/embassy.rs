@@ -0,0 +1,74 @@
+use embassy_sync::blocking_mutex::raw::RawMutex;
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::SpinlockMutex;
+
+/// An [`embassy_sync`] raw mutex backed by the rp2040 hardware spinlock `N`.
+///
+/// This allows using all `embassy-sync` data structures (`Mutex`, `Channel`, `Signal`, ...)
+/// across both cores. While the closure passed to [`RawMutex::lock`] runs, spinlock `N` is claimed.
+///
+/// # Deadlock
+///
+/// Unlike `CriticalSectionRawMutex`, this mutex is not reentrant and does not deactivate
+/// interrupts. Locking it again from within the closure, or from an interrupt preempting the
+/// closure, will cause a deadlock. This also applies to any other user of spinlock `N`.
+///
+/// # Example
+///
+/// ```no_run
+/// use embassy_sync::blocking_mutex::Mutex;
+/// use embassy_sync::channel::Channel;
+/// use rp_spinlockmutex::SpinlockRawMutex;
+///
+/// static COUNTER: Mutex<SpinlockRawMutex<7>, u32> = Mutex::new(0);
+/// static EVENTS: Channel<SpinlockRawMutex<8>, u32, 4> = Channel::new();
+///
+/// COUNTER.lock(|counter| assert_eq!(*counter, 0));
+/// EVENTS.try_send(1).unwrap();
+/// ```
+pub struct SpinlockRawMutex<const N: usize>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    mutex: SpinlockMutex<N, ()>,
+}
+
+impl<const N: usize> SpinlockRawMutex<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates a new raw mutex using spinlock `N`.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            mutex: SpinlockMutex::new(()),
+        }
+    }
+}
+
+impl<const N: usize> Default for SpinlockRawMutex<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// SAFETY: While the closure runs, spinlock `N` is claimed, so no other core can lock the mutex
+//         concurrently. On the same core, only a deadlock (not concurrent access) is possible.
+unsafe impl<const N: usize> RawMutex for SpinlockRawMutex<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[allow(clippy::declare_interior_mutable_const)]
+    const INIT: Self = Self::new();
+
+    #[inline]
+    fn lock<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _guard = self.mutex.lock();
+        f()
+    }
+}
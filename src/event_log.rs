@@ -0,0 +1,104 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::diag::{TraceEvent, TraceKind};
+use crate::LockEvent;
+
+/// The number of events kept, older events are overwritten.
+pub(crate) const CAPACITY: usize = 256;
+
+/// The ring of recorded events, each packed into a single word so that it is written and read
+/// atomically: `(timestamp + 1) << 8 | core << 7 | kind << 5 | number`, or 0 if never written.
+#[allow(clippy::declare_interior_mutable_const)]
+static EVENTS: [AtomicU64; CAPACITY] = {
+    const EMPTY: AtomicU64 = AtomicU64::new(0);
+    [EMPTY; CAPACITY]
+};
+
+/// The timestamp of the next event.
+static NEXT: AtomicU64 = AtomicU64::new(0);
+
+/// The timestamp of the first event not drained yet.
+static DRAINED: AtomicU64 = AtomicU64::new(0);
+
+const ACQUIRED: u64 = 0;
+const RELEASED: u64 = 1;
+const MASKED: u64 = 2;
+const UNMASKED: u64 = 3;
+
+/// Records `event` of spinlock `lock` on the current core.
+///
+/// This only uses atomics, so it can be called while holding any spinlock.
+#[inline]
+pub(crate) fn record(lock: usize, event: LockEvent) {
+    match event {
+        LockEvent::Acquired => push(ACQUIRED, lock as u64),
+        LockEvent::Released => push(RELEASED, lock as u64),
+        LockEvent::Contended => {}
+    }
+}
+
+/// Records that the interrupts in `mask` were enabled or disabled on the current core.
+#[inline]
+pub(crate) fn record_interrupts(mask: u32, enabled: bool) {
+    let kind = if enabled { UNMASKED } else { MASKED };
+    (0..32)
+        .filter(|irq| mask & 1 << irq != 0)
+        .for_each(|irq| push(kind, irq));
+}
+
+#[inline]
+fn push(kind: u64, number: u64) {
+    // Taking the timestamp orders the events as their spinlocks order them: an acquisition
+    // recorded after claiming a spinlock gets a later timestamp than the release recorded
+    // before it was released.
+    let timestamp = NEXT.fetch_add(1, Ordering::Relaxed);
+    let word = (timestamp + 1) << 8 | u64::from(crate::backend::core()) << 7 | kind << 5 | number;
+    EVENTS[timestamp as usize % CAPACITY].store(word, Ordering::Release);
+}
+
+/// Takes all events recorded since the last drain.
+#[inline]
+pub(crate) fn drain() -> Drain {
+    let end = NEXT.load(Ordering::Acquire);
+    let start = DRAINED.swap(end, Ordering::AcqRel);
+    Drain {
+        next: start.max(end.saturating_sub(CAPACITY as u64)),
+        end,
+    }
+}
+
+/// An iterator over the drained events, see [`diag::drain_events`](crate::diag::drain_events).
+#[derive(Debug)]
+pub(crate) struct Drain {
+    next: u64,
+    end: u64,
+}
+
+impl Iterator for Drain {
+    type Item = TraceEvent;
+
+    #[inline]
+    fn next(&mut self) -> Option<TraceEvent> {
+        while self.next < self.end {
+            let timestamp = self.next;
+            self.next += 1;
+            let word = EVENTS[timestamp as usize % CAPACITY].load(Ordering::Acquire);
+            // Skips events that were overwritten by newer ones, or whose slot is not written yet.
+            // The latter are lost, as `DRAINED` is already past them, see `diag::drain_events`.
+            if word >> 8 == timestamp + 1 {
+                let number = (word & 0x1f) as usize;
+                return Some(TraceEvent {
+                    core: (word >> 7 & 1) as u8,
+                    kind: match word >> 5 & 0b11 {
+                        ACQUIRED => TraceKind::Acquired(number),
+                        RELEASED => TraceKind::Released(number),
+                        MASKED => TraceKind::Masked(number as u16),
+                        _ => TraceKind::Unmasked(number as u16),
+                    },
+                    timestamp,
+                });
+            }
+        }
+        None
+    }
+}
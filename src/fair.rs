@@ -0,0 +1,135 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::backend::{fence, Lock, LockStrategy};
+use crate::{CoreId, SpinlockMutex, SpinlockMutexGuard};
+
+/// A mutex that alternates between the two cores when both of them want the lock.
+///
+/// Claiming a hardware spinlock is a race without any fairness: a core releasing a lock and
+/// immediately locking it again often wins against the other core, which can starve it. This
+/// mutex additionally records which core held the lock last. A core that held it last and
+/// locks it again first gives the other core a turn, if that core is waiting in
+/// [`lock`](Self::lock). If it is not, the lock is taken right away, so a core never waits
+/// for a turn the other core does not ask for.
+///
+/// The alternation costs throughput, so only use this for hot locks where a plain
+/// [`SpinlockMutex`] starves one of the cores. Interrupt handlers count as the core they run
+/// on, so they do not get turns of their own.
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::FairSpinlockMutex;
+/// static QUEUE: FairSpinlockMutex<7, [u8; 32]> = FairSpinlockMutex::new([0; 32]);
+///
+/// // Both cores run this loop, and take turns while both of them are in it
+/// loop {
+///     QUEUE.lock()[0] += 1;
+/// }
+/// ```
+pub struct FairSpinlockMutex<const N: usize, T: ?Sized>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Whether each core is waiting in `lock`, only written by the respective core.
+    waiting: [AtomicBool; 2],
+    /// The core that acquired the lock last, only accessed while spinlock `N` is claimed.
+    last: UnsafeCell<Option<CoreId>>,
+    mutex: SpinlockMutex<N, T>,
+}
+
+unsafe impl<const N: usize, T: ?Sized + Send> Sync for FairSpinlockMutex<N, T> where Spinlock<N>: SpinlockValid {}
+
+impl<const N: usize, T> FairSpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates a new hardware based spinlock mutex in an unlocked state ready for use.
+    #[inline]
+    pub const fn new(data: T) -> Self {
+        Self {
+            waiting: [AtomicBool::new(false), AtomicBool::new(false)],
+            last: UnsafeCell::new(None),
+            mutex: SpinlockMutex::new(data),
+        }
+    }
+}
+
+impl<const N: usize, T: ?Sized> FairSpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock, giving the other core a turn first if this core held the lock
+    /// last and the other core is waiting for it.
+    ///
+    /// A turn given up this way is neither reported to the trace hook nor counted by the lock
+    /// statistics as an acquisition. See [`SpinlockMutex::lock`] for details.
+    #[inline]
+    pub fn lock(&self) -> SpinlockMutexGuard<N, T> {
+        let core = CoreId::current();
+        let other = &self.waiting[1 - core as usize];
+        // Only this core writes its flag, so the load and store cannot race.
+        self.waiting[core as usize].store(true, Ordering::Relaxed);
+        let mut spins: u32 = 0;
+        loop {
+            match <Lock<N> as LockStrategy>::try_claim() {
+                Some(lock) => {
+                    if let Some(guard) = self.take_turn(lock, core) {
+                        self.waiting[core as usize].store(false, Ordering::Relaxed);
+                        SpinlockMutex::<N, T>::record_retries(spins);
+                        break guard;
+                    }
+                    // The other core clears its flag as soon as it has the lock.
+                    while other.load(Ordering::Relaxed) {
+                        core::hint::spin_loop();
+                    }
+                }
+                #[cfg(feature = "trace")]
+                None if spins == 0 => crate::trace::emit(N, crate::trace::LockEvent::Contended),
+                None => {}
+            }
+            spins = spins.saturating_add(1);
+        }
+    }
+
+    /// Attempts to acquire this lock without spinning.
+    ///
+    /// Returns `None` if the lock is held, or if this core held it last and the other core is
+    /// waiting for its turn.
+    #[inline]
+    pub fn try_lock(&self) -> Option<SpinlockMutexGuard<N, T>> {
+        let lock = <Lock<N> as LockStrategy>::try_claim();
+        #[cfg(feature = "trace")]
+        if lock.is_none() {
+            crate::trace::emit(N, crate::trace::LockEvent::Contended);
+        }
+        self.take_turn(lock?, CoreId::current())
+    }
+
+    /// Returns a guard for the claimed `lock` if it is the turn of `core`, and releases it
+    /// otherwise. Only a taken turn counts as an acquisition.
+    #[inline]
+    fn take_turn(&self, lock: Lock<N>, core: CoreId) -> Option<SpinlockMutexGuard<N, T>> {
+        // Orders the accesses to `last` after the claim, like the guard does for the data.
+        fence(Ordering::Acquire);
+        // SAFETY: `last` is only accessed while spinlock `N` is claimed, which `lock` proves.
+        let last = unsafe { &mut *self.last.get() };
+        if *last == Some(core) && self.waiting[1 - core as usize].load(Ordering::Relaxed) {
+            // Orders the read of `last` before the release.
+            fence(Ordering::Release);
+            drop(lock);
+            return None;
+        }
+        *last = Some(core);
+        Some(SpinlockMutexGuard::new(lock, self.mutex.data.get()))
+    }
+
+    /// Returns the number of the hardware spinlock used by this mutex.
+    #[inline]
+    pub const fn spinlock_number(&self) -> usize {
+        N
+    }
+}
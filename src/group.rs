@@ -0,0 +1,90 @@
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::SpinlockMutex;
+
+/// A zero-sized marker bundling three distinct spinlock numbers owned by one module.
+///
+/// Keeping the group private to a module and creating that module's mutexes only through it
+/// documents which lock numbers the module uses and keeps them from being spread over the
+/// codebase. It does not change the hardware behavior: the group cannot stop other code from
+/// creating a `SpinlockMutex` with one of its numbers, it merely makes the ownership explicit.
+///
+/// # Example
+///
+/// ```no_run
+/// mod sensors {
+///     use rp_spinlockmutex::{SpinlockGroup, SpinlockMutex};
+///
+///     const LOCKS: SpinlockGroup<3, 4, 5> = SpinlockGroup::new();
+///
+///     pub static TEMPERATURE: SpinlockMutex<3, i16> = LOCKS.first(0);
+///     pub static HUMIDITY: SpinlockMutex<4, u16> = LOCKS.second(0);
+///     pub static PRESSURE: SpinlockMutex<5, u32> = LOCKS.third(0);
+/// }
+/// ```
+pub struct SpinlockGroup<const A: usize, const B: usize, const C: usize>
+where
+    Spinlock<A>: SpinlockValid,
+    Spinlock<B>: SpinlockValid,
+    Spinlock<C>: SpinlockValid,
+{
+    _private: (),
+}
+
+impl<const A: usize, const B: usize, const C: usize> SpinlockGroup<A, B, C>
+where
+    Spinlock<A>: SpinlockValid,
+    Spinlock<B>: SpinlockValid,
+    Spinlock<C>: SpinlockValid,
+{
+    /// Creates a new spinlock group.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lock numbers are not distinct. In a `const` or `static` initializer this
+    /// is a compile-time error.
+    ///
+    /// ```compile_fail
+    /// use rp_spinlockmutex::SpinlockGroup;
+    /// const LOCKS: SpinlockGroup<3, 4, 3> = SpinlockGroup::new(); // ❌ lock 3 used twice ❌
+    /// # let _ = LOCKS;
+    /// ```
+    #[inline]
+    pub const fn new() -> Self {
+        assert!(
+            A != B && A != C && B != C,
+            "spinlock numbers of a group must be distinct"
+        );
+        Self { _private: () }
+    }
+
+    /// Creates a new mutex using the first spinlock of this group.
+    #[inline]
+    pub const fn first<T>(&self, data: T) -> SpinlockMutex<A, T> {
+        SpinlockMutex::new(data)
+    }
+
+    /// Creates a new mutex using the second spinlock of this group.
+    #[inline]
+    pub const fn second<T>(&self, data: T) -> SpinlockMutex<B, T> {
+        SpinlockMutex::new(data)
+    }
+
+    /// Creates a new mutex using the third spinlock of this group.
+    #[inline]
+    pub const fn third<T>(&self, data: T) -> SpinlockMutex<C, T> {
+        SpinlockMutex::new(data)
+    }
+}
+
+impl<const A: usize, const B: usize, const C: usize> Default for SpinlockGroup<A, B, C>
+where
+    Spinlock<A>: SpinlockValid,
+    Spinlock<B>: SpinlockValid,
+    Spinlock<C>: SpinlockValid,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
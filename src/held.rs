@@ -0,0 +1,148 @@
+use core::mem::ManuallyDrop;
+use core::sync::atomic::Ordering;
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::backend::{fence, Lock};
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+/// Proof that hardware spinlock `N` is claimed, obtained from [`SpinlockMutexGuard::into_raw`].
+///
+/// This is the right to release the spinlock, detached from any data. It can be passed around
+/// freely, e.g. from the function that locked the mutex to the one that finishes the critical
+/// section, and turned back into a guard with [`SpinlockMutexGuard::from_raw`]. Dropping it
+/// releases the spinlock, so the lock never leaks, even if the guard is not reconstructed.
+#[must_use = "if unused the spinlock will immediately unlock"]
+pub struct RawHeld<const N: usize>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    lock: Lock<N>,
+}
+
+impl<const N: usize> RawHeld<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Returns the number of the hardware spinlock held by this token.
+    #[inline]
+    pub const fn spinlock_number(&self) -> usize {
+        N
+    }
+
+    #[inline]
+    fn into_lock(self) -> Lock<N> {
+        let held = ManuallyDrop::new(self);
+        // SAFETY: `held` is never dropped, so the spinlock is now owned by the returned value alone.
+        unsafe { core::ptr::read(&held.lock) }
+    }
+}
+
+impl<const N: usize> Drop for RawHeld<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        #[cfg(feature = "trace")]
+        crate::trace::emit(N, crate::LockEvent::Released);
+        // Like for a guard, all accesses to the data must be complete before `lock` is dropped
+        // afterwards, which releases the spinlock.
+        fence(Ordering::Release);
+    }
+}
+
+impl<const N: usize, T: ?Sized> SpinlockMutexGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Turns the guard into a [`RawHeld`] token, keeping the spinlock claimed.
+    ///
+    /// This separates the right to release the lock from the borrow of the mutex, for handing
+    /// the lock off between code sections the borrow checker cannot relate. The data cannot be
+    /// accessed until the guard is reconstructed with [`from_raw`](Self::from_raw).
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::{RawHeld, SpinlockMutex, SpinlockMutexGuard};
+    /// static FRAME: SpinlockMutex<7, [u8; 64]> = SpinlockMutex::new([0; 64]);
+    ///
+    /// fn begin_frame() -> RawHeld<7> {
+    ///     SpinlockMutexGuard::into_raw(FRAME.lock())
+    /// }
+    ///
+    /// fn end_frame(held: RawHeld<7>) {
+    ///     SpinlockMutexGuard::from_raw(held, &FRAME)[0] = 1;
+    /// }
+    ///
+    /// end_frame(begin_frame());
+    /// ```
+    #[inline]
+    pub fn into_raw(guard: Self) -> RawHeld<N> {
+        let guard = ManuallyDrop::new(guard);
+        RawHeld {
+            // SAFETY: `guard` is never dropped, so the spinlock is now owned by the token alone.
+            lock: unsafe { core::ptr::read(&guard._lock) },
+        }
+    }
+
+    /// Reconstructs a guard for `mutex` from a token of [`into_raw`](Self::into_raw).
+    ///
+    /// As all mutexes using spinlock `N` are protected by the same hardware lock, `mutex` does
+    /// not have to be the mutex the token was created from.
+    #[inline]
+    pub fn from_raw(held: RawHeld<N>, mutex: &SpinlockMutex<N, T>) -> Self {
+        #[cfg(all(feature = "registry", debug_assertions))]
+        // SAFETY: `held` proves that spinlock `N` is claimed, and the data of `mutex` is valid.
+        unsafe {
+            crate::registry::register::<N, T>(mutex.data.get())
+        };
+        SpinlockMutexGuard {
+            _lock: held.into_lock(),
+            data: mutex.data.get(),
+        }
+    }
+
+    /// Splits the guard into the claimed [`Spinlock`] of `rp2040_hal` and a pointer to the
+    /// protected data, for interop with code using the HAL's spinlocks directly.
+    ///
+    /// Dropping the returned spinlock releases it. The pointer may only be dereferenced while
+    /// it is claimed. The HAL's spinlocks do not order memory accesses, so issue a
+    /// [`fence(Release)`](core::sync::atomic::fence) after the last access before dropping it,
+    /// as a guard would. Neither the `trace` hook nor the `metrics` of this crate observe the
+    /// release. Only available on the rp2040, as the HAL's spinlocks do not exist off-target.
+    ///
+    /// ```no_run
+    /// use core::sync::atomic::{fence, Ordering};
+    /// use rp_spinlockmutex::{SpinlockMutex, SpinlockMutexGuard};
+    /// static COUNTER: SpinlockMutex<7, u32> = SpinlockMutex::new(0);
+    ///
+    /// let (spinlock, data) = SpinlockMutexGuard::into_hal(COUNTER.lock());
+    /// // SAFETY: `spinlock` is still claimed.
+    /// unsafe { *data += 1 };
+    /// fence(Ordering::Release);
+    /// drop(spinlock);
+    /// ```
+    #[cfg(target_arch = "arm")]
+    #[inline]
+    pub fn into_hal(guard: Self) -> (Spinlock<N>, *mut T) {
+        let data = guard.data;
+        (Self::into_raw(guard).into_lock(), data)
+    }
+
+    /// Creates a guard from a claimed [`Spinlock`] of `rp2040_hal` and a pointer to the data
+    /// it protects, e.g. as returned by [`into_hal`](Self::into_hal).
+    ///
+    /// The guard behaves like one returned by [`SpinlockMutex::lock`], including the memory
+    /// ordering, the `trace` hook and the `metrics`.
+    ///
+    /// # Safety
+    ///
+    /// `data` must be valid for reads and writes for as long as the guard lives, and must only
+    /// ever be accessed while spinlock `N` is claimed, e.g. point into a
+    /// `SpinlockMutex<N, T>`.
+    #[cfg(target_arch = "arm")]
+    #[inline]
+    pub unsafe fn from_hal(spinlock: Spinlock<N>, data: *mut T) -> Self {
+        Self::new(spinlock, data)
+    }
+}
@@ -0,0 +1,307 @@
+#[cfg(all(feature = "defmt", target_arch = "arm"))]
+use core::mem::ManuallyDrop;
+use core::ops::{Deref, DerefMut};
+#[cfg(all(feature = "defmt", target_arch = "arm"))]
+use core::sync::atomic::AtomicU32;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+#[cfg(target_arch = "arm")]
+use crate::SystemTimer;
+use crate::{MonotonicCycles, SpinlockMutex, SpinlockMutexGuard};
+
+/// The installed hook as a `fn(usize, u32, u32)`, or null.
+static HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Installs `hook` to be called when a [`HoldTimeGuard`] was held longer than its budget,
+/// replacing any previously installed hook.
+///
+/// The hook receives the number of the spinlock, the time the lock was held and the budget,
+/// both in ticks of the guard's clock, i.e. microseconds for [`SpinlockMutex::lock_budgeted`],
+/// saturated to `u32::MAX`. It is called on drop of the guard, while the spinlock is still
+/// held, so it must not lock the same mutex.
+///
+/// Without a hook, exceeding the budget panics in debug builds and is ignored in release builds.
+#[inline]
+pub fn set_hold_time_hook(hook: fn(usize, u32, u32)) {
+    HOOK.store(hook as *mut (), Ordering::Relaxed);
+}
+
+/// The warning threshold of each spinlock for [`SpinlockMutex::lock_monitored`] in ticks of
+/// the guard's clock, `u32::MAX` if none is set.
+#[cfg(all(feature = "defmt", target_arch = "arm"))]
+static WARN_THRESHOLDS: [AtomicU32; 32] = {
+    #[allow(clippy::declare_interior_mutable_const)]
+    const NONE: AtomicU32 = AtomicU32::new(u32::MAX);
+    [NONE; 32]
+};
+
+/// Sets the hold time above which a [`MonitoredGuard`] of spinlock `N` logs a warning with
+/// `defmt`, in microseconds for [`SpinlockMutex::lock_monitored`] or ticks of the clock for
+/// [`SpinlockMutex::lock_monitored_with`].
+///
+/// The threshold applies to all mutexes using spinlock `N`. Until it is set, holds of any
+/// length are accepted silently.
+///
+/// ```no_run
+/// use rp_spinlockmutex::set_hold_warning_threshold;
+/// set_hold_warning_threshold::<7>(50);
+/// ```
+#[cfg(all(feature = "defmt", target_arch = "arm"))]
+#[inline]
+pub fn set_hold_warning_threshold<const N: usize>(max_us: u32)
+where
+    Spinlock<N>: SpinlockValid,
+{
+    WARN_THRESHOLDS[N].store(max_us, Ordering::Relaxed);
+}
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock and returns a guard that checks on drop whether the lock was held
+    /// for more than `max_us` microseconds.
+    ///
+    /// This helps validating worst-case execution time assumptions of critical sections during
+    /// testing. On a violation, the hook installed with [`set_hold_time_hook`] is called, or, if
+    /// there is none, debug builds panic.
+    ///
+    /// The hold time is measured with the rp2040's [`SystemTimer`], as the Cortex-M0+ has no
+    /// DWT cycle counter. Waiting for the lock does not count towards the budget. Use
+    /// [`lock_budgeted_with`](Self::lock_budgeted_with) for another clock.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static SAMPLES: SpinlockMutex<7, [u16; 64]> = SpinlockMutex::new([0; 64]);
+    ///
+    /// let mut samples = SAMPLES.lock_budgeted(10);
+    /// samples.sort_unstable(); // panics on drop in debug builds if this took more than 10 µs
+    /// ```
+    #[cfg(target_arch = "arm")]
+    #[inline]
+    pub fn lock_budgeted(&self, max_us: u32) -> HoldTimeGuard<'static, N, T> {
+        self.lock_budgeted_with(&SystemTimer, u64::from(max_us))
+    }
+
+    /// Acquires the mutex lock and returns a guard that checks on drop whether the lock was held
+    /// for more than `max` ticks of `clock`.
+    ///
+    /// Like [`lock_budgeted`](Self::lock_budgeted), but with a custom clock, e.g. one advanced
+    /// by hand in host tests:
+    ///
+    /// ```no_run
+    /// use core::sync::atomic::{AtomicU64, Ordering};
+    /// use rp_spinlockmutex::{MonotonicCycles, SpinlockMutex};
+    ///
+    /// struct TestClock(AtomicU64);
+    ///
+    /// impl MonotonicCycles for TestClock {
+    ///     fn now(&self) -> u64 {
+    ///         self.0.load(Ordering::Relaxed)
+    ///     }
+    /// }
+    ///
+    /// let clock = TestClock(AtomicU64::new(0));
+    /// let mutex: SpinlockMutex<7, u32> = SpinlockMutex::new(0);
+    ///
+    /// let guard = mutex.lock_budgeted_with(&clock, 10);
+    /// clock.0.store(20, Ordering::Relaxed);
+    /// drop(guard); // ❌ panics in debug builds ❌
+    /// ```
+    #[inline]
+    pub fn lock_budgeted_with<'c, C: MonotonicCycles + Sync>(&self, clock: &'c C, max: u64) -> HoldTimeGuard<'c, N, T> {
+        let guard = self.lock();
+        HoldTimeGuard {
+            guard,
+            clock,
+            start: clock.now(),
+            max,
+        }
+    }
+}
+
+#[cfg(all(feature = "defmt", target_arch = "arm"))]
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock and returns a guard that logs a `defmt` warning on drop if the
+    /// lock was held longer than the threshold of spinlock `N`.
+    ///
+    /// Unlike [`lock_budgeted`](Self::lock_budgeted), a long hold never panics, so this is
+    /// meant for continuous monitoring of production firmware. The threshold is set with
+    /// [`set_hold_warning_threshold`]. The warning names the spinlock number, the name set
+    /// with [`diag::set_lock_names`](crate::diag::set_lock_names) and the hold time. It is
+    /// logged after the spinlock is released, so logging does not prolong the critical
+    /// section.
+    ///
+    /// As for `lock_budgeted`, the hold time is measured in microseconds with the
+    /// [`SystemTimer`]. Use [`lock_monitored_with`](Self::lock_monitored_with) for another clock.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::{set_hold_warning_threshold, SpinlockMutex};
+    /// static SAMPLES: SpinlockMutex<7, [u16; 64]> = SpinlockMutex::new([0; 64]);
+    ///
+    /// set_hold_warning_threshold::<7>(10);
+    ///
+    /// let mut samples = SAMPLES.lock_monitored();
+    /// samples.sort_unstable(); // logs a warning if this took more than 10 µs
+    /// ```
+    #[inline]
+    pub fn lock_monitored(&self) -> MonitoredGuard<'static, N, T> {
+        self.lock_monitored_with(&SystemTimer)
+    }
+
+    /// Acquires the mutex lock and returns a guard that logs a `defmt` warning on drop if the
+    /// lock was held longer than the threshold of spinlock `N`, in ticks of `clock`.
+    ///
+    /// Like [`lock_monitored`](Self::lock_monitored), but with a custom clock.
+    #[inline]
+    pub fn lock_monitored_with<'c, C: MonotonicCycles + Sync>(&self, clock: &'c C) -> MonitoredGuard<'c, N, T> {
+        let guard = self.lock();
+        MonitoredGuard {
+            guard: ManuallyDrop::new(guard),
+            clock,
+            start: clock.now(),
+        }
+    }
+}
+
+/// A guard obtained from [`SpinlockMutex::lock_budgeted`].
+///
+/// It behaves like a [`SpinlockMutexGuard`], but checks its hold time against a budget on drop.
+#[must_use = "if unused the SpinlockMutex will immediately unlock"]
+pub struct HoldTimeGuard<'c, const N: usize, T: ?Sized>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    guard: SpinlockMutexGuard<N, T>,
+    clock: &'c (dyn MonotonicCycles + Sync),
+    start: u64,
+    max: u64,
+}
+
+impl<const N: usize, T: ?Sized> Drop for HoldTimeGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        let held = self.clock.now().saturating_sub(self.start);
+        if held <= self.max {
+            return;
+        }
+
+        let hook = HOOK.load(Ordering::Relaxed);
+        if !hook.is_null() {
+            // SAFETY: Non-null values are only ever stored by `set_hold_time_hook`, from a `fn(usize, u32, u32)`.
+            let hook: fn(usize, u32, u32) = unsafe { core::mem::transmute(hook) };
+            let saturate = |ticks: u64| u32::try_from(ticks).unwrap_or(u32::MAX);
+            hook(N, saturate(held), saturate(self.max));
+        } else if cfg!(debug_assertions) {
+            panic!(
+                "SpinlockMutex<{}, _>{} was held for {} ticks, exceeding its budget of {} ticks",
+                N,
+                crate::diag::NameSuffix(N),
+                held,
+                self.max
+            );
+        }
+    }
+}
+
+impl<const N: usize, T: ?Sized> Deref for HoldTimeGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<const N: usize, T: ?Sized> DerefMut for HoldTimeGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+/// A guard obtained from [`SpinlockMutex::lock_monitored`].
+///
+/// It behaves like a [`SpinlockMutexGuard`], but logs a warning on drop if it was held longer
+/// than the threshold of its spinlock.
+#[cfg(all(feature = "defmt", target_arch = "arm"))]
+#[must_use = "if unused the SpinlockMutex will immediately unlock"]
+pub struct MonitoredGuard<'c, const N: usize, T: ?Sized>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    guard: ManuallyDrop<SpinlockMutexGuard<N, T>>,
+    clock: &'c (dyn MonotonicCycles + Sync),
+    start: u64,
+}
+
+#[cfg(all(feature = "defmt", target_arch = "arm"))]
+impl<const N: usize, T: ?Sized> Drop for MonitoredGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        let held = self.clock.now().saturating_sub(self.start);
+        // SAFETY: `guard` is not used after this.
+        unsafe { ManuallyDrop::drop(&mut self.guard) };
+
+        let max = WARN_THRESHOLDS[N].load(Ordering::Relaxed);
+        if held <= u64::from(max) {
+            return;
+        }
+        match crate::diag::lock_name(N) {
+            Some(name) => defmt::warn!(
+                "SpinlockMutex<{=usize}, _> ('{=str}') was held for {=u64} ticks, exceeding {=u32} ticks",
+                N,
+                name,
+                held,
+                max
+            ),
+            None => defmt::warn!(
+                "SpinlockMutex<{=usize}, _> was held for {=u64} ticks, exceeding {=u32} ticks",
+                N,
+                held,
+                max
+            ),
+        }
+    }
+}
+
+#[cfg(all(feature = "defmt", target_arch = "arm"))]
+impl<const N: usize, T: ?Sized> Deref for MonitoredGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+#[cfg(all(feature = "defmt", target_arch = "arm"))]
+impl<const N: usize, T: ?Sized> DerefMut for MonitoredGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
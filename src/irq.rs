@@ -0,0 +1,112 @@
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+
+use cortex_m::interrupt::CriticalSection;
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::backend::{Lock, LockStrategy};
+use crate::{CapabilityGuard, SpinlockMutex, SpinlockMutexGuard};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Whether a [`SpinlockMutex`] is shared with interrupt handlers, either [`NotIrqShared`] or
+/// [`IrqShared`].
+///
+/// The trait is sealed and cannot be implemented outside of this crate.
+pub trait IrqSharing: private::Sealed {}
+
+/// Marks a [`SpinlockMutex`] that is not used by interrupt handlers, the default.
+pub enum NotIrqShared {}
+
+/// Marks a [`SpinlockMutex`] that is shared with interrupt handlers.
+///
+/// An interrupt handler locking a mutex held by the code it preempted spins forever, as that
+/// code cannot continue to release the lock. Such a mutex must only be locked with the
+/// interrupts of the current core disabled. A `SpinlockMutex<N, T, IrqShared>` enforces
+/// this at compile time: it lacks [`lock`](SpinlockMutex::lock) and all other methods
+/// holding the lock with interrupts enabled, and only offers `lock_in_free` and the
+/// non-blocking `try_lock`, which both take the token of a `cortex_m::interrupt::free`
+/// section.
+///
+/// ```no_run
+/// use cortex_m::interrupt;
+/// use rp_spinlockmutex::{IrqShared, SpinlockMutex};
+/// static EVENTS: SpinlockMutex<7, u32, IrqShared> = SpinlockMutex::new_irq_shared(0);
+///
+/// // In the main loop of either core, and in interrupt handlers on both
+/// interrupt::free(|cs| *EVENTS.lock_in_free(cs) += 1);
+/// ```
+///
+/// ```compile_fail
+/// use rp_spinlockmutex::{IrqShared, SpinlockMutex};
+/// static EVENTS: SpinlockMutex<7, u32, IrqShared> = SpinlockMutex::new_irq_shared(0);
+///
+/// *EVENTS.lock() += 1; // ❌ could deadlock with an interrupt handler ❌
+/// ```
+///
+/// ```compile_fail
+/// use rp_spinlockmutex::{IrqShared, SpinlockMutex};
+/// static EVENTS: SpinlockMutex<7, u32, IrqShared> = SpinlockMutex::new_irq_shared(0);
+///
+/// *EVENTS.try_lock().unwrap() += 1; // ❌ the guard needs a critical section ❌
+/// ```
+pub enum IrqShared {}
+
+impl private::Sealed for NotIrqShared {}
+impl IrqSharing for NotIrqShared {}
+impl private::Sealed for IrqShared {}
+impl IrqSharing for IrqShared {}
+
+impl<const N: usize, T> SpinlockMutex<N, T, IrqShared>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates a new hardware based spinlock mutex shared with interrupt handlers, in an
+    /// unlocked state ready for use.
+    #[inline]
+    pub const fn new_irq_shared(data: T) -> Self {
+        Self {
+            #[cfg(feature = "core-pinning")]
+            pinned: None,
+            _irq: PhantomData,
+            data: UnsafeCell::new(data),
+        }
+    }
+}
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T, IrqShared>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock inside a `cortex_m::interrupt::free` section.
+    ///
+    /// See [`SpinlockMutex::lock_in_free`] for details.
+    #[inline]
+    pub fn lock_in_free<'cs>(&'cs self, _cs: &'cs CriticalSection) -> CapabilityGuard<'cs, N, T, CriticalSection> {
+        CapabilityGuard::new(SpinlockMutexGuard::new(SpinlockMutex::<N, T>::claim(), self.data.get()))
+    }
+
+    /// Attempts to acquire this lock without spinning, inside a `cortex_m::interrupt::free`
+    /// section.
+    ///
+    /// Like [`lock_in_free`](Self::lock_in_free), this requires the token `cs`, as an
+    /// interrupt handler locking the mutex while the returned guard is held would spin forever.
+    /// See [`SpinlockMutex::try_lock`] for details.
+    #[inline]
+    pub fn try_lock<'cs>(&'cs self, _cs: &'cs CriticalSection) -> Option<CapabilityGuard<'cs, N, T, CriticalSection>> {
+        let lock = <Lock<N> as LockStrategy>::try_claim();
+        #[cfg(feature = "trace")]
+        if lock.is_none() {
+            crate::trace::emit(N, crate::LockEvent::Contended);
+        }
+        lock.map(|lock| CapabilityGuard::new(SpinlockMutexGuard::new(lock, self.data.get())))
+    }
+
+    /// Returns the number of the hardware spinlock used by this mutex.
+    #[inline]
+    pub const fn spinlock_number(&self) -> usize {
+        N
+    }
+}
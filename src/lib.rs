@@ -22,11 +22,120 @@
 //! ```
 #![no_std]
 
+#[cfg(all(feature = "std-testing", not(target_arch = "arm")))]
+extern crate std;
+
 use core::cell::UnsafeCell;
-use core::ops::{Deref, DerefMut};
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut, Index, IndexMut, Range};
+use core::sync::atomic::Ordering;
 
 use rp2040_hal::sio::{Spinlock, SpinlockValid};
 
+use backend::{fence, Lock, LockStrategy};
+
+mod abort;
+mod backend;
+mod budget;
+mod capability;
+mod ceiling;
+mod cell;
+mod clock;
+mod contended;
+mod core_local;
+#[cfg(target_arch = "arm")]
+mod deadlock;
+pub mod diag;
+mod double_buffer;
+#[cfg(feature = "embassy")]
+mod embassy;
+#[cfg(all(feature = "test-trace", not(target_arch = "arm")))]
+mod event_log;
+mod fair;
+mod group;
+mod held;
+#[cfg(feature = "hold-time")]
+mod hold_time;
+mod irq;
+mod lock_guard;
+mod macros;
+mod masking;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod multicore;
+mod numeric;
+mod order;
+mod pinned;
+mod pool;
+#[cfg(feature = "embedded-hal")]
+mod probe;
+#[cfg(feature = "lock_api")]
+mod raw;
+#[cfg(all(feature = "registry", debug_assertions))]
+mod registry;
+mod scoped;
+mod semaphore;
+mod seqlock;
+mod slice;
+pub mod spin;
+#[cfg(feature = "trace")]
+mod trace;
+mod uninit;
+mod update;
+mod valid_lock;
+#[cfg(feature = "embedded-hal")]
+mod watchdog;
+
+pub use abort::{panic_release_all, AbortSafeGuard};
+#[cfg(not(target_arch = "arm"))]
+pub use backend::{HostNvic, HostSpinlock};
+pub use budget::check_lock_budget;
+pub use capability::CapabilityGuard;
+pub use ceiling::CeilingGuard;
+pub use cell::SpinlockCell;
+pub use clock::MonotonicCycles;
+#[cfg(target_arch = "arm")]
+pub use clock::SystemTimer;
+pub use contended::ContendedBy;
+pub use core_local::CoreLocal;
+#[cfg(target_arch = "arm")]
+pub use deadlock::DeadlockAction;
+pub use double_buffer::{DoubleBuffer, DoubleBufferGuard};
+#[cfg(feature = "embassy")]
+pub use embassy::SpinlockRawMutex;
+pub use fair::FairSpinlockMutex;
+pub use group::SpinlockGroup;
+pub use held::RawHeld;
+#[cfg(feature = "hold-time")]
+pub use hold_time::{set_hold_time_hook, HoldTimeGuard};
+#[cfg(all(feature = "hold-time", feature = "defmt", target_arch = "arm"))]
+pub use hold_time::{set_hold_warning_threshold, MonitoredGuard};
+pub use irq::{IrqShared, IrqSharing, NotIrqShared};
+pub use lock_guard::LockGuard;
+#[doc(hidden)]
+pub use macros::run_locked;
+pub use masking::MaskingGuard;
+pub use multicore::CoreId;
+pub use numeric::Numeric;
+pub use order::{OrderedGuard, OrderedSpinlockMutex};
+pub use pinned::PinnedSpinlockMutex;
+pub use pool::PoolGuard;
+#[cfg(feature = "embedded-hal")]
+pub use probe::ProbeGuard;
+#[cfg(feature = "lock_api")]
+pub use raw::{LockApiMutex, LockApiMutexGuard, RawSpinlock};
+pub use scoped::ScopedSetGuard;
+pub use semaphore::{Permit, SpinlockSemaphore};
+pub use seqlock::SpinlockSeqLock;
+pub use slice::SliceGuard;
+#[cfg(feature = "trace")]
+pub use trace::{clear_trace_hook, set_trace_hook, LockEvent};
+pub use uninit::UninitSpinlockMutex;
+pub use update::UpdateBuilder;
+pub use valid_lock::{LockNumber, ValidLock};
+#[cfg(feature = "embedded-hal")]
+pub use watchdog::WatchdogFeedingGuard;
+
 /// A mutex implementation based on the rp2040 hardware spinlock.
 ///
 /// The rp2040 provides 32 hardware spinlocks. The lock number (0 to 31)
@@ -37,11 +146,31 @@ use rp2040_hal::sio::{Spinlock, SpinlockValid};
 /// These hardware spinlocks are global, so e.g. if you try to lock a
 /// `SpinlockMutex<7, _>` then any other part of your application using
 /// `SpinlockMutex<7, _>` or [`rp2040_hal::sio::Spinlock<7>`][`Spinlock`]
-/// will contend for the same lock.
+/// will contend for the same lock. With the `registry` feature enabled,
+/// debug builds panic on the first lock of a mutex whose spinlock number
+/// is already used by a mutex protecting a different type.
+///
+/// On targets other than ARM, e.g. in host unit tests, the hardware spinlocks
+/// are replaced by an atomic emulation, `HostSpinlock`.
 ///
 /// If both cores try to claim the lock on the same clock cycle,
 /// core 0 will acquire the lock, which may lead to lock starvation.
 ///
+/// With `panic = "abort"`, a panic while a guard is held leaks the lock,
+/// as the guard is never dropped. See [`SpinlockMutex::lock_abort_safe`]
+/// for a way to release it from the panic handler.
+///
+/// Like `std::sync::Mutex`, the mutex is `Sync` whenever `T` is `Send`.
+/// As only one core can access the data at a time, types with unsynchronized
+/// interior mutability like [`Cell`](core::cell::Cell) or
+/// [`RefCell`](core::cell::RefCell) can be shared between the cores this way.
+/// A guard, which lends out `&T` to whoever it is shared with, is only `Sync`
+/// if `T` is.
+///
+/// The third parameter marks whether the mutex is shared with interrupt
+/// handlers. A `SpinlockMutex<N, T, IrqShared>` can only be locked with the
+/// interrupts of the current core disabled, see [`IrqShared`].
+///
 /// # Example
 ///
 /// Fully working code can be found in `examples/`.
@@ -62,15 +191,24 @@ use rp2040_hal::sio::{Spinlock, SpinlockValid};
 ///
 /// assert_eq!(*MUTEX.lock(), 20);
 /// ```
-pub struct SpinlockMutex<const N: usize, T: ?Sized>
+pub struct SpinlockMutex<const N: usize, T: ?Sized, I: IrqSharing = NotIrqShared>
 where
     Spinlock<N>: SpinlockValid,
 {
+    #[cfg(feature = "core-pinning")]
+    pinned: Option<CoreId>,
+    _irq: PhantomData<I>,
     data: UnsafeCell<T>,
 }
 
-unsafe impl<const N: usize, T: ?Sized + Send> Send for SpinlockMutex<N, T> where Spinlock<N>: SpinlockValid {}
-unsafe impl<const N: usize, T: ?Sized + Send> Sync for SpinlockMutex<N, T> where Spinlock<N>: SpinlockValid {}
+unsafe impl<const N: usize, T: ?Sized + Send, I: IrqSharing> Send for SpinlockMutex<N, T, I> where
+    Spinlock<N>: SpinlockValid
+{
+}
+unsafe impl<const N: usize, T: ?Sized + Send, I: IrqSharing> Sync for SpinlockMutex<N, T, I> where
+    Spinlock<N>: SpinlockValid
+{
+}
 
 impl<const N: usize, T> SpinlockMutex<N, T>
 where
@@ -78,18 +216,181 @@ where
 {
     /// Creates a new hardware based spinlock mutex in an unlocked state ready for use.
     ///
+    /// # Large values
+    ///
+    /// In a `static`, the initializer is evaluated at compile time, including calls to other
+    /// `const fn`s. The initial value is placed in `.data` (or `.bss` if it is all zeros) and
+    /// copied there by the startup code, so even large buffers cost neither stack nor runtime.
+    /// Only a mutex created at runtime, e.g. a local or one from [`new_with`](Self::new_with),
+    /// is built on the stack. With the `core-pinning` feature, the mutex is slightly larger
+    /// than `T`, for the pinned core and padding.
+    ///
     /// # Examples
     ///
     /// ```no_run
     /// use rp_spinlockmutex::SpinlockMutex;
     /// let mutex: SpinlockMutex<7, i32> = SpinlockMutex::new(42);
     /// ```
+    ///
+    /// A 4 KiB lookup table, computed at compile time:
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    ///
+    /// const fn squares() -> [u32; 1024] {
+    ///     let mut table = [0; 1024];
+    ///     let mut i = 0;
+    ///     while i < table.len() {
+    ///         table[i] = (i * i) as u32;
+    ///         i += 1;
+    ///     }
+    ///     table
+    /// }
+    ///
+    /// static SQUARES: SpinlockMutex<7, [u32; 1024]> = SpinlockMutex::new(squares());
+    /// ```
     #[inline]
     pub const fn new(data: T) -> Self {
         Self {
+            #[cfg(feature = "core-pinning")]
+            pinned: None,
+            _irq: PhantomData,
             data: UnsafeCell::new(data),
         }
     }
+
+    /// Creates a new hardware based spinlock mutex that may only be locked from `core`.
+    ///
+    /// This is meant for mutexes that are conceptually owned by one core and only
+    /// protect data shared with its interrupt handlers. In debug builds, locking such a
+    /// mutex from the other core panics, which surfaces architectural mistakes early
+    /// instead of as subtle races. In release builds it behaves like a regular mutex.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::{CoreId, SpinlockMutex};
+    /// static MUTEX: SpinlockMutex<7, i32> = SpinlockMutex::new_pinned(CoreId::Core0, 0);
+    ///
+    /// *MUTEX.lock() += 1; // ✅ on core 0, panics on core 1 in debug builds
+    /// ```
+    #[cfg(feature = "core-pinning")]
+    #[inline]
+    pub const fn new_pinned(core: CoreId, data: T) -> Self {
+        Self {
+            pinned: Some(core),
+            _irq: PhantomData,
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Creates a new hardware based spinlock mutex initialized with the return value of `f`.
+    ///
+    /// This is a convenience for complex initializers, as it avoids a temporary binding.
+    /// It is not a `const fn`, so a `static` mutex still has to be created with [`new`](Self::new).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// let mutex: SpinlockMutex<7, [u32; 8]> = SpinlockMutex::new_with(|| {
+    ///     let mut table = [0; 8];
+    ///     for (i, entry) in table.iter_mut().enumerate() {
+    ///         *entry = 1 << i;
+    ///     }
+    ///     table
+    /// });
+    /// ```
+    #[inline]
+    pub fn new_with(f: impl FnOnce() -> T) -> Self {
+        Self::new(f())
+    }
+
+    /// Acquires the mutex lock and returns a clone of the current value alongside the guard.
+    ///
+    /// The clone is taken while the lock is held, so it reflects the exact state before any
+    /// modification made through the returned guard. This is useful for logging the before/after
+    /// of a read-modify-write without claiming the lock twice.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// let mutex: SpinlockMutex<7, i32> = SpinlockMutex::new(42);
+    ///
+    /// let (before, mut guard) = mutex.lock_logging();
+    /// *guard += 1;
+    ///
+    /// assert_eq!((before, *guard), (42, 43));
+    /// ```
+    #[inline]
+    pub fn lock_logging(&self) -> (T, SpinlockMutexGuard<N, T>)
+    where
+        T: Clone,
+    {
+        let guard = self.lock();
+        (T::clone(&guard), guard)
+    }
+
+    /// Replaces the protected value with `T::default()` and returns the previous value.
+    ///
+    /// Reading and resetting happen in a single critical section, so no update from the other
+    /// core can get lost in between. This is handy for accumulators that are read out and
+    /// cleared periodically.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// let events: SpinlockMutex<7, u32> = SpinlockMutex::new(0);
+    ///
+    /// *events.lock() += 3;
+    ///
+    /// assert_eq!(events.reset(), 3);
+    /// assert_eq!(*events.lock(), 0);
+    /// ```
+    #[inline]
+    pub fn reset(&self) -> T
+    where
+        T: Default,
+    {
+        core::mem::take(&mut *self.lock())
+    }
+
+    /// Like [`reset`](Self::reset), but returns `None` instead of blocking if the lock is held.
+    #[inline]
+    pub fn try_reset(&self) -> Option<T>
+    where
+        T: Default,
+    {
+        self.try_lock().map(|mut guard| core::mem::take(&mut *guard))
+    }
+
+    /// Acquires the mutex lock, replaces the contents of the storage with those of `new` and
+    /// returns the previous contents as a cell.
+    ///
+    /// This relocates data between buffers at the storage level: the new contents are moved
+    /// into place with [`core::ptr::replace`] while the spinlock is claimed, so the other core
+    /// either sees the old or the new contents, never a mix of both.
+    ///
+    /// This is equivalent to `core::mem::replace(&mut *mutex.lock(), new.into_inner())`, which
+    /// is the more idiomatic choice for plain values.
+    ///
+    /// # Deadlock
+    ///
+    /// Like [`lock`](Self::lock), this deadlocks if the current core already holds the lock.
+    ///
+    /// ```no_run
+    /// use core::cell::UnsafeCell;
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static FRAME: SpinlockMutex<7, [u8; 4]> = SpinlockMutex::new([0; 4]);
+    ///
+    /// let old = FRAME.replace_cell(UnsafeCell::new([1, 2, 3, 4]));
+    ///
+    /// assert_eq!(old.into_inner(), [0; 4]);
+    /// ```
+    #[inline]
+    pub fn replace_cell(&self, new: UnsafeCell<T>) -> UnsafeCell<T> {
+        let _guard = self.lock();
+        // SAFETY: `_guard` proves that the lock is claimed, so nothing else accesses the data.
+        UnsafeCell::new(unsafe { core::ptr::replace(self.data.get(), new.into_inner()) })
+    }
 }
 
 impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
@@ -98,6 +399,9 @@ where
 {
     /// Acquires the mutex lock, blocking the current thread until the lock is available.
     ///
+    /// The lock is held until the guard is dropped. The [`locked!`](crate::locked) macro
+    /// bounds the critical section to a block instead, which is the recommended style.
+    ///
     /// # Deadlock
     ///
     /// Repeatedly calling while holding the lock will cause a deadlock.
@@ -110,50 +414,626 @@ where
     /// let guard_1 = mutex.lock();
     /// let guard_2 = mutex.lock(); // ❌ deadlock ❌
     /// ```
+    ///
+    /// # Iterating
+    ///
+    /// The guard dereferences to the data, so the methods of a locked collection, such as
+    /// `iter` and `iter_mut` of arrays, slices and `heapless::Vec`, are available on it
+    /// directly. Bind the guard to keep the lock held for the whole loop, instead of locking
+    /// once per element:
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static SAMPLES: SpinlockMutex<7, [u16; 16]> = SpinlockMutex::new([0; 16]);
+    /// # fn run_on_core1(f: impl FnOnce()) {}
+    /// # fn read_adc(channel: usize) -> u16 { 0 }
+    ///
+    /// run_on_core1(|| {
+    ///     let mut samples = SAMPLES.lock();
+    ///     for (i, sample) in samples.iter_mut().enumerate() {
+    ///         *sample = read_adc(i);
+    ///     }
+    /// });
+    ///
+    /// // Sees either none or all of the samples of core 1
+    /// let sum: u32 = SAMPLES.lock().iter().map(|&sample| u32::from(sample)).sum();
+    /// ```
     #[inline]
+    #[track_caller]
     pub fn lock(&self) -> SpinlockMutexGuard<N, T> {
-        SpinlockMutexGuard {
-            _lock: Spinlock::<N>::claim(),
-            data: self.data.get(),
-        }
+        self.check_core();
+        SpinlockMutexGuard::new(Self::claim(), self.data.get())
     }
 
+    #[inline]
+    #[track_caller]
     pub fn try_lock(&self) -> Option<SpinlockMutexGuard<N, T>> {
-        Spinlock::<N>::try_claim().map(|lock| SpinlockMutexGuard {
-            _lock: lock,
-            data: self.data.get(),
-        })
+        self.check_core();
+        let lock = <Lock<N> as LockStrategy>::try_claim();
+        #[cfg(feature = "trace")]
+        if lock.is_none() {
+            trace::emit(N, trace::LockEvent::Contended);
+        }
+        lock.map(|lock| SpinlockMutexGuard::new(lock, self.data.get()))
+    }
+
+    /// Acquires the mutex lock and narrows the guard to the part of the data returned by `f`,
+    /// e.g. a variant of an enum.
+    ///
+    /// If `f` returns `None`, the lock is released again and `None` is returned. This is a
+    /// shorthand for locking and then calling [`SpinlockMutexGuard::map`] only if the data is
+    /// in the expected shape.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    ///
+    /// enum State {
+    ///     Idle,
+    ///     Running { progress: u32 },
+    /// }
+    ///
+    /// static STATE: SpinlockMutex<7, State> = SpinlockMutex::new(State::Idle);
+    ///
+    /// let running = STATE.lock_as(|state| match state {
+    ///     State::Running { progress } => Some(progress),
+    ///     State::Idle => None,
+    /// });
+    /// if let Some(mut progress) = running {
+    ///     *progress += 1;
+    /// }
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lock_as<U: ?Sized>(&self, f: impl FnOnce(&mut T) -> Option<&mut U>) -> Option<SpinlockMutexGuard<N, U>> {
+        let mut guard = self.lock();
+        let data: *mut U = f(&mut guard)?;
+        Some(SpinlockMutexGuard::with_data(guard, data))
+    }
+
+    /// Acquires the mutex lock, blocking until it is available or `should_abort` returns `true`.
+    ///
+    /// `should_abort` is called after every failed attempt to claim the spinlock, so it can be
+    /// used to wire in timeouts, FIFO messages, GPIO levels or any other stop condition.
+    /// Returns `None` if the acquisition was aborted.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// let mutex: SpinlockMutex<7, i32> = SpinlockMutex::new(42);
+    ///
+    /// let mut attempts = 0;
+    /// let guard = mutex.lock_or_abort(|| {
+    ///     attempts += 1;
+    ///     attempts > 1000
+    /// });
+    /// ```
+    #[inline]
+    pub fn lock_or_abort(&self, mut should_abort: impl FnMut() -> bool) -> Option<SpinlockMutexGuard<N, T>> {
+        let mut spins: u32 = 0;
+        loop {
+            if let Some(guard) = self.try_lock() {
+                Self::record_retries(spins);
+                break Some(guard);
+            }
+            if should_abort() {
+                break None;
+            }
+            spins = spins.saturating_add(1);
+        }
     }
 
     #[inline]
     pub fn unlock(guard: SpinlockMutexGuard<N, T>) {
         core::mem::drop(guard);
     }
+
+    /// Acquires the mutex lock, runs `f` on the data and releases the lock again, for use in
+    /// async code.
+    ///
+    /// Holding a guard across an `.await` keeps the spinlock claimed while the task is
+    /// suspended, which blocks the other core and deadlocks any other task of the same core
+    /// locking the mutex. As `f` is synchronous, it cannot await, so the lock is never held
+    /// across a suspension point. The lock is acquired when the returned future is first
+    /// polled, which completes right away.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static READINGS: SpinlockMutex<7, [u16; 8]> = SpinlockMutex::new([0; 8]);
+    ///
+    /// async fn latest() -> u16 {
+    ///     READINGS.with_lock_async(|readings| readings[0]).await
+    /// }
+    /// ```
+    #[inline]
+    pub async fn with_lock_async<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        f(&mut self.lock())
+    }
+
+    /// Returns a mutable reference to the underlying data without claiming the spinlock.
+    ///
+    /// This is intended for the single-threaded boot phase, e.g. to initialize a `static`
+    /// mutex before core 1 is started, where acquiring the hardware lock is pure overhead.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that no other access to the data happens while the returned
+    /// reference is alive. In particular:
+    ///
+    /// - core 1 must not be running yet (or must provably never touch this mutex),
+    /// - no interrupt handler accessing this mutex may fire,
+    /// - no [`SpinlockMutexGuard`] for this mutex may exist, and
+    /// - this function must not be called again until the returned reference is dead.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static MUTEX: SpinlockMutex<7, [u8; 4]> = SpinlockMutex::new([0; 4]);
+    ///
+    /// // SAFETY: Core 1 has not been started yet and interrupts are not enabled.
+    /// unsafe { MUTEX.borrow_unchecked() }.copy_from_slice(&[1, 2, 3, 4]);
+    /// ```
+    #[inline]
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn borrow_unchecked(&self) -> &mut T {
+        &mut *self.data.get()
+    }
+
+    /// Returns the number of the hardware spinlock used by this mutex.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// let mutex: SpinlockMutex<7, i32> = SpinlockMutex::new(42);
+    ///
+    /// assert_eq!(mutex.spinlock_number(), 7);
+    /// ```
+    #[inline]
+    pub const fn spinlock_number(&self) -> usize {
+        N
+    }
+
+    /// Panics in debug builds if the mutex is pinned to the other core.
+    #[inline(always)]
+    #[track_caller]
+    fn check_core(&self) {
+        #[cfg(all(feature = "core-pinning", debug_assertions))]
+        if let Some(core) = self.pinned {
+            assert_eq!(
+                CoreId::current(),
+                core,
+                "SpinlockMutex<{}, _>{} is pinned to another core",
+                N,
+                diag::NameSuffix(N)
+            );
+        }
+    }
+
+    /// Reports to the lock statistics that a retrying lock call needed `spins` failed attempts of
+    /// [`try_lock`](Self::try_lock) before the one that claimed spinlock `N`. The trace hook is
+    /// already called by each failed attempt.
+    ///
+    /// Must be called while the current core holds spinlock `N`, as the counters are only
+    /// written under it.
+    #[inline(always)]
+    pub(crate) fn record_retries(spins: u32) {
+        #[cfg(feature = "metrics")]
+        // SAFETY: The caller holds spinlock `N`.
+        unsafe {
+            metrics::record_spins(N, spins)
+        };
+        #[cfg(not(feature = "metrics"))]
+        let _ = spins;
+    }
+
+    /// Claims spinlock `N`, reporting contention to the trace hook and the lock statistics.
+    #[inline(always)]
+    fn claim() -> Lock<N> {
+        #[cfg(any(feature = "trace", feature = "metrics"))]
+        {
+            let mut spins: u32 = 0;
+            let lock = loop {
+                if let Some(lock) = <Lock<N> as LockStrategy>::try_claim() {
+                    break lock;
+                }
+                #[cfg(feature = "trace")]
+                if spins == 0 {
+                    trace::emit(N, trace::LockEvent::Contended);
+                }
+                spins = spins.saturating_add(1);
+            };
+            #[cfg(feature = "metrics")]
+            // SAFETY: `lock` proves that spinlock `N` is claimed.
+            unsafe {
+                metrics::record_spins(N, spins)
+            };
+            lock
+        }
+        #[cfg(not(any(feature = "trace", feature = "metrics")))]
+        <Lock<N> as LockStrategy>::claim()
+    }
+}
+
+impl<const N: usize, R, F: ?Sized + FnMut() -> R> SpinlockMutex<N, F>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock and calls the protected closure while holding it.
+    ///
+    /// Together with unsized coercion this allows storing a trait object callable from either core,
+    /// e.g. a `&SpinlockMutex<N, dyn FnMut() + Send>` created from a reference to a mutex
+    /// protecting a concrete closure.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    ///
+    /// let mut count = 0;
+    /// let counter = SpinlockMutex::<7, _>::new(move || {
+    ///     count += 1;
+    ///     count
+    /// });
+    /// let callback: &SpinlockMutex<7, dyn FnMut() -> u32 + Send> = &counter;
+    ///
+    /// fn assert_sync<T: ?Sized + Sync>(_: &T) {}
+    /// assert_sync(callback);
+    ///
+    /// assert_eq!(callback.call_locked(), 1);
+    /// assert_eq!((*callback.lock())(), 2);
+    /// ```
+    ///
+    /// # Deadlock
+    ///
+    /// The closure must not lock this mutex (or any other user of spinlock `N`) itself.
+    #[inline]
+    pub fn call_locked(&self) -> R {
+        (*self.lock())()
+    }
+}
+
+impl<const N: usize, T> SpinlockMutex<N, Option<T>>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Takes the protected value out of the mutex, leaving `None` in its place.
+    ///
+    /// This is a one-shot handoff between the cores: even if both cores race to take the value,
+    /// exactly one of them gets `Some`, and every later call returns `None` (until a new value
+    /// is stored).
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static CONFIG: SpinlockMutex<7, Option<[u8; 16]>> = SpinlockMutex::new(Some([0; 16]));
+    ///
+    /// // On whichever core comes first
+    /// if let Some(config) = CONFIG.take_once() {
+    ///     // apply config
+    /// }
+    /// ```
+    #[inline]
+    pub fn take_once(&self) -> Option<T> {
+        self.lock().take()
+    }
+}
+
+impl<const N: usize, T, const CAP: usize> SpinlockMutex<N, [T; CAP]>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock and returns a guard for the elements in `range` only.
+    ///
+    /// This suits buffers where different parts of the application own different windows.
+    /// The whole mutex stays locked while the guard is held.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds or its start is greater than its end. The range is
+    /// checked before the lock is acquired.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static BUFFER: SpinlockMutex<7, [u8; 64]> = SpinlockMutex::new([0; 64]);
+    ///
+    /// let mut header = BUFFER.lock_range(0..8);
+    /// header.copy_from_slice(b"RP2040\0\0");
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lock_range(&self, range: Range<usize>) -> SpinlockMutexGuard<N, [T]> {
+        check_range(&range, CAP);
+        SpinlockMutexGuard::map(self.lock(), |data| &mut data[range])
+    }
+
+    /// Like [`lock_range`](Self::lock_range), but returns `None` instead of spinning if the
+    /// lock is held.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds or its start is greater than its end.
+    #[inline]
+    #[track_caller]
+    pub fn try_lock_range(&self, range: Range<usize>) -> Option<SpinlockMutexGuard<N, [T]>> {
+        check_range(&range, CAP);
+        Some(SpinlockMutexGuard::map(self.try_lock()?, |data| &mut data[range]))
+    }
+}
+
+/// Panics if `range` is not a valid range into a slice of length `len`.
+#[inline]
+#[track_caller]
+fn check_range(range: &Range<usize>, len: usize) {
+    assert!(
+        range.start <= range.end && range.end <= len,
+        "range {:?} out of bounds for a buffer of length {}",
+        range,
+        len
+    );
+}
+
+#[cfg(feature = "heapless")]
+impl<const N: usize, T, const CAP: usize> SpinlockMutex<N, heapless::Vec<T, CAP>>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Moves as many elements of the protected vector as fit into `out`, appending them, and
+    /// returns how many were moved.
+    ///
+    /// The elements are moved from the front, so if `out` runs out of capacity, the remaining
+    /// elements stay queued in their order and are taken by the next call. The lock is only
+    /// held for copying the elements, which allows processing a batch of queued elements
+    /// outside of the critical section, without blocking the other core for the duration.
+    ///
+    /// ```no_run
+    /// use heapless::Vec;
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static QUEUE: SpinlockMutex<7, Vec<u32, 16>> = SpinlockMutex::new(Vec::new());
+    ///
+    /// QUEUE.lock().push(1).unwrap();
+    /// QUEUE.lock().push(2).unwrap();
+    ///
+    /// let mut batch: Vec<u32, 16> = Vec::new();
+    /// QUEUE.drain_into(&mut batch);
+    ///
+    /// assert_eq!(batch, [1, 2]);
+    /// assert!(QUEUE.lock().is_empty());
+    /// ```
+    #[inline]
+    pub fn drain_into<const OUT: usize>(&self, out: &mut heapless::Vec<T, OUT>) -> usize {
+        let mut queue = self.lock();
+        let (len, start) = (queue.len(), out.len());
+        let moved = len.min(OUT - start);
+        // SAFETY: The first `moved` elements are moved into the spare capacity of `out` and
+        //         the remaining ones to the front, and both lengths are updated accordingly,
+        //         so every element is owned by exactly one of the vectors. Nothing in between
+        //         can panic.
+        unsafe {
+            core::ptr::copy_nonoverlapping(queue.as_ptr(), out.as_mut_ptr().add(start), moved);
+            out.set_len(start + moved);
+            core::ptr::copy(queue.as_ptr().add(moved), queue.as_mut_ptr(), len - moved);
+            queue.set_len(len - moved);
+        }
+        moved
+    }
+}
+
+impl<const N: usize, T> From<UnsafeCell<T>> for SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates a new unlocked mutex protecting the contents of `cell`.
+    #[inline]
+    fn from(cell: UnsafeCell<T>) -> Self {
+        Self {
+            #[cfg(feature = "core-pinning")]
+            pinned: None,
+            _irq: PhantomData,
+            data: cell,
+        }
+    }
+}
+
+impl<const N: usize, T> From<SpinlockMutex<N, T>> for UnsafeCell<T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Consumes the mutex, returning its underlying storage.
+    ///
+    /// No lock can be held, as taking the mutex by value requires that no guard borrows it.
+    #[inline]
+    fn from(mutex: SpinlockMutex<N, T>) -> Self {
+        mutex.data
+    }
 }
 
 /// A SpinlockMutexGuard allows the holder to access the protected data of a mutex.
 /// If this guard is dropped, the mutex will be unlocked automatically. The lock can
 /// also be lifted manually with [`SpinlockMutex::unlock`].
 ///
+/// All accesses to the protected data made through the guard happen after the
+/// spinlock was claimed and before it is released, both in the eyes of the
+/// compiler and of the other core.
+///
+/// # Drop order
 ///
+/// All guard types of this crate release the hardware spinlock as the very
+/// last step of their drop. Any additional work a guard does on drop (clearing
+/// the [`AbortSafeGuard`] record, feeding the watchdog of a
+/// `WatchdogFeedingGuard`, ...) happens while the spinlock is still claimed,
+/// so the other core never observes a half-released lock.
+///
+/// The exceptions are the [`MaskingGuard`] and the [`CeilingGuard`], which
+/// re-enable their interrupts after releasing the spinlock, as an interrupt handler
+/// could otherwise spin on a lock held by the core it preempted.
+/// The `MonitoredGuard` likewise logs long holds only after releasing, so logging
+/// does not prolong the critical section.
 #[must_use = "if unused the SpinlockMutex will immediately unlock"]
 pub struct SpinlockMutexGuard<const N: usize, T: ?Sized>
 where
     Spinlock<N>: SpinlockValid,
 {
-    _lock: Spinlock<N>,
+    _lock: Lock<N>,
     data: *mut T,
 }
 
 unsafe impl<const N: usize, T: ?Sized + Send> Send for SpinlockMutexGuard<N, T> where Spinlock<N>: SpinlockValid {}
 unsafe impl<const N: usize, T: ?Sized + Sync> Sync for SpinlockMutexGuard<N, T> where Spinlock<N>: SpinlockValid {}
 
+impl<const N: usize, T: ?Sized> SpinlockMutexGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn new(lock: Lock<N>, data: *mut T) -> Self {
+        // Claiming the spinlock is a volatile read of the SIO register, which neither the
+        // compiler nor the bus orders against regular memory accesses. Without this fence,
+        // accesses to the data could be hoisted above the claim. (The pico-sdk issues the
+        // same DMB in `spin_lock_blocking`.)
+        fence(Ordering::Acquire);
+        #[cfg(all(feature = "registry", debug_assertions))]
+        // SAFETY: `lock` proves that spinlock `N` is claimed, and `data` points into a mutex.
+        unsafe {
+            registry::register::<N, T>(data)
+        };
+        #[cfg(feature = "metrics")]
+        // SAFETY: `lock` proves that spinlock `N` is claimed.
+        unsafe {
+            metrics::record_acquisition(N)
+        };
+        #[cfg(feature = "trace")]
+        trace::emit(N, trace::LockEvent::Acquired);
+        Self { _lock: lock, data }
+    }
+
+    /// Temporarily releases the spinlock to give the other core a chance to acquire it, then
+    /// re-acquires it.
+    ///
+    /// This is meant for long loops that hold the lock for their whole duration, to reduce the
+    /// other core's lock starvation without ending the guard's scope. After releasing, this
+    /// pauses for a few cycles, so a core waiting in [`SpinlockMutex::lock`] wins the race for
+    /// the lock even if the current core is core 0.
+    ///
+    /// **Note:** Other code may lock the mutex during the bump, and observe or modify the
+    /// protected data. Make sure the data is in a consistent state before calling this, and do
+    /// not rely on it being unchanged afterwards.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static LOG: SpinlockMutex<7, [u8; 1024]> = SpinlockMutex::new([0; 1024]);
+    ///
+    /// let mut log = LOG.lock();
+    /// for chunk in 0..16 {
+    ///     log[chunk * 64..(chunk + 1) * 64].fill(0);
+    ///     log.bump();
+    /// }
+    /// ```
+    #[inline]
+    pub fn bump(&mut self) {
+        #[cfg(feature = "trace")]
+        trace::emit(N, trace::LockEvent::Released);
+        fence(Ordering::Release);
+        // SAFETY: `_lock` proves that the current core claimed spinlock `N`. The released
+        // `Spinlock` is forgotten below, and the data is not accessed until it is re-claimed.
+        unsafe { <Lock<N> as LockStrategy>::release() };
+        for _ in 0..8 {
+            core::hint::spin_loop();
+        }
+        core::mem::forget(core::mem::replace(&mut self._lock, SpinlockMutex::<N, T>::claim()));
+        fence(Ordering::Acquire);
+        #[cfg(feature = "metrics")]
+        // SAFETY: `_lock` proves that spinlock `N` is claimed again.
+        unsafe {
+            metrics::record_acquisition(N)
+        };
+        #[cfg(feature = "trace")]
+        trace::emit(N, trace::LockEvent::Acquired);
+    }
+
+    /// Returns a mutable reference to the protected data, bound to the lifetime of the guard.
+    ///
+    /// This is the same as `&mut *guard`, but makes the intent explicit where the reference is
+    /// handed on. It is an associated function, so it does not shadow a `get_mut` method of `T`.
+    #[inline]
+    pub fn get_mut(guard: &mut Self) -> &mut T {
+        guard
+    }
+
+    /// Returns a raw pointer to the protected data, e.g. for passing it to C.
+    ///
+    /// The pointer is only valid while the guard is alive, as the other core may access the data
+    /// once the spinlock is released. It is an associated function, so it does not shadow an
+    /// `as_mut_ptr` method of `T` (e.g. of slices and arrays).
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::{SpinlockMutex, SpinlockMutexGuard};
+    /// static BUFFER: SpinlockMutex<7, [u8; 64]> = SpinlockMutex::new([0; 64]);
+    ///
+    /// extern "C" {
+    ///     fn fill_buffer(buffer: *mut [u8; 64]);
+    /// }
+    ///
+    /// let mut buffer = BUFFER.lock();
+    /// // SAFETY: The guard outlives the call, which does not keep the pointer.
+    /// unsafe { fill_buffer(SpinlockMutexGuard::as_mut_ptr(&mut buffer)) };
+    /// ```
+    #[inline]
+    pub fn as_mut_ptr(guard: &mut Self) -> *mut T {
+        guard.data
+    }
+
+    /// Makes a guard for a part of the protected data, e.g. a field or a sub-slice.
+    ///
+    /// The spinlock stays claimed until the returned guard is dropped. If `f` panics, the
+    /// spinlock is released. It is an associated function, so it does not shadow a `map`
+    /// method of `T`.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::{SpinlockMutex, SpinlockMutexGuard};
+    /// static POSITION: SpinlockMutex<7, (i32, i32)> = SpinlockMutex::new((0, 0));
+    ///
+    /// let mut x = SpinlockMutexGuard::map(POSITION.lock(), |(x, _)| x);
+    /// *x += 1;
+    /// ```
+    #[inline]
+    pub fn map<U: ?Sized>(mut guard: Self, f: impl FnOnce(&mut T) -> &mut U) -> SpinlockMutexGuard<N, U> {
+        let data: *mut U = f(&mut guard);
+        Self::with_data(guard, data)
+    }
+
+    /// Moves the claimed spinlock of `guard` into a guard for `data`, which has to point into
+    /// the data of `guard`.
+    #[inline]
+    fn with_data<U: ?Sized>(guard: Self, data: *mut U) -> SpinlockMutexGuard<N, U> {
+        let guard = core::mem::ManuallyDrop::new(guard);
+        SpinlockMutexGuard {
+            // SAFETY: `guard` is never dropped, so the spinlock is now owned by this guard alone.
+            _lock: unsafe { core::ptr::read(&guard._lock) },
+            data,
+        }
+    }
+
+    /// Returns the number of the hardware spinlock held by this guard.
+    #[inline]
+    pub const fn spinlock_number(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize, T: ?Sized> Drop for SpinlockMutexGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        #[cfg(feature = "trace")]
+        trace::emit(N, trace::LockEvent::Released);
+        // Pairs with the acquire fence in `new`: all accesses to the data must be complete
+        // before `_lock` is dropped afterwards, which releases the spinlock.
+        fence(Ordering::Release);
+    }
+}
+
 impl<const N: usize, T: ?Sized> Deref for SpinlockMutexGuard<N, T>
 where
     Spinlock<N>: SpinlockValid,
 {
     type Target = T;
 
+    #[inline]
     fn deref(&self) -> &T {
         // SAFETY: There can only ever be one instance of a mutex guard at the same time.
         //         Therefore it's safe to hand out borrows.
@@ -165,9 +1045,71 @@ impl<const N: usize, T: ?Sized> DerefMut for SpinlockMutexGuard<N, T>
 where
     Spinlock<N>: SpinlockValid,
 {
+    #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
         // SAFETY: There can only ever be one instance of a mutex guard at the same time.
         //         Therefore it's safe to hand out borrows.
         unsafe { &mut *self.data }
     }
 }
+
+impl<const N: usize, T: ?Sized + Index<Idx>, Idx> Index<Idx> for SpinlockMutexGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Output = T::Output;
+
+    #[inline]
+    fn index(&self, index: Idx) -> &Self::Output {
+        &(**self)[index]
+    }
+}
+
+impl<const N: usize, T: ?Sized + IndexMut<Idx>, Idx> IndexMut<Idx> for SpinlockMutexGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn index_mut(&mut self, index: Idx) -> &mut Self::Output {
+        &mut (**self)[index]
+    }
+}
+
+/// Writes into the protected buffer, e.g. with `write!(LOG.lock(), ...)` into a shared log
+/// buffer written from either core.
+impl<const N: usize, T: ?Sized + core::fmt::Write> core::fmt::Write for SpinlockMutexGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        (**self).write_str(s)
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> core::fmt::Result {
+        (**self).write_char(c)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<const N: usize, T: ?Sized + ufmt::uDebug> ufmt::uDebug for SpinlockMutexGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn fmt<W: ufmt::uWrite + ?Sized>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error> {
+        T::fmt(self, f)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<const N: usize, T: ?Sized + ufmt::uDisplay> ufmt::uDisplay for SpinlockMutexGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn fmt<W: ufmt::uWrite + ?Sized>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error> {
+        T::fmt(self, f)
+    }
+}
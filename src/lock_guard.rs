@@ -0,0 +1,114 @@
+use core::ops::DerefMut;
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{AbortSafeGuard, CapabilityGuard, DoubleBufferGuard, PoolGuard, SpinlockMutexGuard};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A guard giving exclusive access to a `T` while it holds a hardware spinlock.
+///
+/// This trait is implemented by all guards of this crate that dereference to the protected
+/// data. It allows crates built on top of this one to return `impl LockGuard<T>` and keep
+/// the concrete guard type, including its spinlock number, out of their public API.
+///
+/// The trait is sealed and cannot be implemented outside of this crate.
+///
+/// ```no_run
+/// use rp_spinlockmutex::{LockGuard, SpinlockMutex};
+///
+/// pub struct Counter {
+///     count: SpinlockMutex<7, u32>,
+/// }
+///
+/// impl Counter {
+///     pub fn lock(&self) -> impl LockGuard<u32> + '_ {
+///         self.count.lock()
+///     }
+/// }
+/// ```
+pub trait LockGuard<T: ?Sized>: DerefMut<Target = T> + private::Sealed {}
+
+impl<const N: usize, T: ?Sized> private::Sealed for SpinlockMutexGuard<N, T> where Spinlock<N>: SpinlockValid {}
+impl<const N: usize, T: ?Sized> LockGuard<T> for SpinlockMutexGuard<N, T> where Spinlock<N>: SpinlockValid {}
+
+impl<const N: usize, T: ?Sized> private::Sealed for AbortSafeGuard<N, T> where Spinlock<N>: SpinlockValid {}
+impl<const N: usize, T: ?Sized> LockGuard<T> for AbortSafeGuard<N, T> where Spinlock<N>: SpinlockValid {}
+
+impl<const N: usize, T: ?Sized, C: ?Sized> private::Sealed for CapabilityGuard<'_, N, T, C> where
+    Spinlock<N>: SpinlockValid
+{
+}
+impl<const N: usize, T: ?Sized, C: ?Sized> LockGuard<T> for CapabilityGuard<'_, N, T, C> where Spinlock<N>: SpinlockValid
+{}
+
+impl<T: ?Sized> private::Sealed for PoolGuard<T> {}
+impl<T: ?Sized> LockGuard<T> for PoolGuard<T> {}
+
+impl<const N: usize, const ORDER: u32, T: ?Sized> private::Sealed for crate::OrderedGuard<N, ORDER, T> where
+    Spinlock<N>: SpinlockValid
+{
+}
+impl<const N: usize, const ORDER: u32, T: ?Sized> LockGuard<T> for crate::OrderedGuard<N, ORDER, T> where
+    Spinlock<N>: SpinlockValid
+{
+}
+
+#[cfg(feature = "hold-time")]
+impl<const N: usize, T: ?Sized> private::Sealed for crate::HoldTimeGuard<'_, N, T> where Spinlock<N>: SpinlockValid {}
+#[cfg(feature = "hold-time")]
+impl<const N: usize, T: ?Sized> LockGuard<T> for crate::HoldTimeGuard<'_, N, T> where Spinlock<N>: SpinlockValid {}
+
+#[cfg(all(feature = "hold-time", feature = "defmt", target_arch = "arm"))]
+impl<const N: usize, T: ?Sized> private::Sealed for crate::MonitoredGuard<'_, N, T> where Spinlock<N>: SpinlockValid {}
+#[cfg(all(feature = "hold-time", feature = "defmt", target_arch = "arm"))]
+impl<const N: usize, T: ?Sized> LockGuard<T> for crate::MonitoredGuard<'_, N, T> where Spinlock<N>: SpinlockValid {}
+
+impl<const N: usize, T: ?Sized> private::Sealed for crate::CeilingGuard<N, T> where Spinlock<N>: SpinlockValid {}
+impl<const N: usize, T: ?Sized> LockGuard<T> for crate::CeilingGuard<N, T> where Spinlock<N>: SpinlockValid {}
+
+impl<const N: usize, T: ?Sized> private::Sealed for crate::MaskingGuard<N, T> where Spinlock<N>: SpinlockValid {}
+impl<const N: usize, T: ?Sized> LockGuard<T> for crate::MaskingGuard<N, T> where Spinlock<N>: SpinlockValid {}
+
+impl<const A: usize, const B: usize, T> private::Sealed for DoubleBufferGuard<A, B, T>
+where
+    Spinlock<A>: SpinlockValid,
+    Spinlock<B>: SpinlockValid,
+{
+}
+impl<const A: usize, const B: usize, T> LockGuard<T> for DoubleBufferGuard<A, B, T>
+where
+    Spinlock<A>: SpinlockValid,
+    Spinlock<B>: SpinlockValid,
+{
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<const N: usize, T: ?Sized, W: embedded_hal::watchdog::Watchdog> private::Sealed
+    for crate::WatchdogFeedingGuard<'_, N, T, W>
+where
+    Spinlock<N>: SpinlockValid,
+{
+}
+#[cfg(feature = "embedded-hal")]
+impl<const N: usize, T: ?Sized, W: embedded_hal::watchdog::Watchdog> LockGuard<T>
+    for crate::WatchdogFeedingGuard<'_, N, T, W>
+where
+    Spinlock<N>: SpinlockValid,
+{
+}
+
+#[cfg(feature = "embedded-hal")]
+impl<const N: usize, T: ?Sized, P: embedded_hal::digital::v2::OutputPin> private::Sealed
+    for crate::ProbeGuard<'_, N, T, P>
+where
+    Spinlock<N>: SpinlockValid,
+{
+}
+#[cfg(feature = "embedded-hal")]
+impl<const N: usize, T: ?Sized, P: embedded_hal::digital::v2::OutputPin> LockGuard<T> for crate::ProbeGuard<'_, N, T, P> where
+    Spinlock<N>: SpinlockValid
+{
+}
@@ -0,0 +1,335 @@
+/// Declares a `static` [`SpinlockMutex`](crate::SpinlockMutex) placed in a specific linker section.
+///
+/// Placing frequently locked data in one of the core-local scratch banks
+/// (`SCRATCH_X` at `0x2004_0000` and `SCRATCH_Y` at `0x2004_1000`, 4 KiB each)
+/// or in a dedicated SRAM bank keeps its accesses from contending with
+/// other traffic on the main SRAM banks.
+///
+/// # Valid sections
+///
+/// The section must be defined by your linker script, as the default
+/// `memory.x` of an rp2040 project only contains `BOOT2`, `FLASH` and `RAM`.
+///
+/// **Note:** `cortex-m-rt` only initializes `.data` and `.bss`. A mutex in any
+/// other section has to be initialized by your startup code, e.g. by copying
+/// the section's load image in a `#[pre_init]` function (as the pico-sdk does
+/// for the scratch banks), otherwise it starts out with whatever the memory
+/// contained. Subsections like `.data.hot` are initialized by `cortex-m-rt`.
+///
+/// A matching `memory.x` could look like this:
+///
+/// ```text
+/// MEMORY {
+///     BOOT2 : ORIGIN = 0x10000000, LENGTH = 0x100
+///     FLASH : ORIGIN = 0x10000100, LENGTH = 2048K - 0x100
+///     RAM   : ORIGIN = 0x20000000, LENGTH = 256K
+///     SCRATCH_X : ORIGIN = 0x20040000, LENGTH = 4K
+/// }
+///
+/// SECTIONS {
+///     .scratch_x : ALIGN(4) {
+///         __sscratch_x = .;
+///         *(.scratch_x .scratch_x.*);
+///         . = ALIGN(4);
+///         __escratch_x = .;
+///     } > SCRATCH_X AT > FLASH
+///     __siscratch_x = LOADADDR(.scratch_x);
+/// } INSERT AFTER .bss;
+/// ```
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::spinlock_mutex_in_section;
+///
+/// spinlock_mutex_in_section!(pub SAMPLES, 7, [u16; 64], [0; 64], ".scratch_x");
+///
+/// SAMPLES.lock()[0] = 42;
+/// ```
+#[macro_export]
+macro_rules! spinlock_mutex_in_section {
+    ($vis:vis $name:ident, $n:expr, $t:ty, $init:expr, $section:literal) => {
+        #[link_section = $section]
+        $vis static $name: $crate::SpinlockMutex<{ $n }, $t> = $crate::SpinlockMutex::new($init);
+    };
+}
+
+/// Declares a struct whose fields are protected by separate [`SpinlockMutex`](crate::SpinlockMutex)es.
+///
+/// Locking a single `SpinlockMutex<N, (A, B)>` serializes all access to both fields, even if
+/// one core only needs `A` and the other only needs `B`. This macro instead gives every field
+/// its own spinlock number, so accesses to different fields run in parallel and only accesses
+/// to the same field serialize. Each field is a regular `SpinlockMutex` that can be locked on
+/// its own, and the generated `lock_all` method locks all fields at once.
+///
+/// The spinlock numbers are checked with [`check_lock_budget`](crate::check_lock_budget), so
+/// using a number twice, or the reserved spinlock 31, is a compile-time error. Other mutexes
+/// of the application may still use the same numbers, which makes them contend as usual.
+///
+/// ```compile_fail
+/// use rp_spinlockmutex::spinlock_fields;
+///
+/// spinlock_fields! {
+///     struct Sensors {
+///         temperature: 3 => i16,
+///         humidity: 3 => u16, // ❌ lock 3 used twice ❌
+///     }
+/// }
+/// ```
+///
+/// # Deadlock
+///
+/// `lock_all` claims the spinlocks in declaration order. Code locking several fields on its
+/// own has to use the same order, otherwise the two cores can deadlock each other.
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::spinlock_fields;
+///
+/// spinlock_fields! {
+///     pub struct Sensors {
+///         pub temperature: 3 => i16,
+///         pub humidity: 4 => u16,
+///     }
+/// }
+///
+/// static SENSORS: Sensors = Sensors::new(0, 0);
+///
+/// // Core 0 and core 1 can lock different fields in parallel
+/// *SENSORS.temperature.lock() = 21;
+/// *SENSORS.humidity.lock() = 40;
+///
+/// // Taking a consistent snapshot of all fields
+/// let (temperature, humidity) = SENSORS.lock_all();
+/// ```
+#[macro_export]
+macro_rules! spinlock_fields {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field_vis:vis $field:ident: $n:literal => $t:ty),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* $field_vis $field: $crate::SpinlockMutex<$n, $t>,)+
+        }
+
+        const _: () = $crate::check_lock_budget(&[$($n),+]);
+
+        impl $name {
+            /// Creates the struct with all fields in an unlocked state.
+            #[inline]
+            $vis const fn new($($field: $t),+) -> Self {
+                Self {
+                    $($field: $crate::SpinlockMutex::new($field),)+
+                }
+            }
+
+            /// Locks all fields in declaration order.
+            #[inline]
+            $vis fn lock_all(&self) -> ($($crate::SpinlockMutexGuard<$n, $t>,)+) {
+                ($(self.$field.lock(),)+)
+            }
+        }
+    };
+}
+
+/// Declares a struct holding a pool of `T`s, each protected by its own [`SpinlockMutex`](crate::SpinlockMutex).
+///
+/// An array `[SpinlockMutex<N, T>; 4]` serializes all accesses on spinlock `N`, as all its
+/// elements share the same lock. This macro instead gives every slot the next spinlock number
+/// of the list, so accesses to different slots run in parallel on the two cores. As the
+/// guards of the slots have different types, the generated methods return a [`PoolGuard`](crate::PoolGuard):
+///
+/// * `lock_slot(index)` locks the slot at `index`, panicking if it is out of bounds.
+/// * `try_lock_slot(index)` does the same without spinning, returning `None` if it is locked.
+/// * `try_lock_any()` locks the first slot that is not locked yet, if any.
+///
+/// Every slot starts out with the value of the initializer expression, which is evaluated once
+/// per slot. The spinlock numbers are checked with [`check_lock_budget`](crate::check_lock_budget),
+/// so using a number twice, or the reserved spinlock 31, is a compile-time error.
+///
+/// ```compile_fail
+/// use rp_spinlockmutex::spinlock_pool;
+///
+/// spinlock_pool! {
+///     struct Buffers: [u8; 64] = [0; 64];
+///     spinlocks [3, 4, 3]; // ❌ lock 3 used twice ❌
+/// }
+/// ```
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::spinlock_pool;
+///
+/// spinlock_pool! {
+///     pub struct Buffers: [u8; 64] = [0; 64];
+///     spinlocks [3, 4, 5, 6];
+/// }
+///
+/// static BUFFERS: Buffers = Buffers::new();
+///
+/// // Core 0 and core 1 can fill different buffers in parallel
+/// BUFFERS.lock_slot(0)[0] = 42;
+///
+/// if let Some(mut buffer) = BUFFERS.try_lock_any() {
+///     buffer.fill(0xff);
+/// }
+/// ```
+#[macro_export]
+macro_rules! spinlock_pool {
+    (@ty $t:ty;) => { () };
+    (@ty $t:ty; $n:literal $(, $rest:literal)*) => {
+        ($crate::SpinlockMutex<$n, $t>, $crate::spinlock_pool!(@ty $t; $($rest),*))
+    };
+
+    (@new $init:expr;) => { () };
+    (@new $init:expr; $n:literal $(, $rest:literal)*) => {
+        ($crate::SpinlockMutex::new($init), $crate::spinlock_pool!(@new $init; $($rest),*))
+    };
+
+    (@lock $slots:expr, $index:expr;) => {
+        panic!("spinlock pool slot index out of bounds")
+    };
+    (@lock $slots:expr, $index:expr; $n:literal $(, $rest:literal)*) => {
+        if $index == 0 {
+            $crate::PoolGuard::from($slots.0.lock())
+        } else {
+            $crate::spinlock_pool!(@lock $slots.1, $index - 1; $($rest),*)
+        }
+    };
+
+    (@try_lock $slots:expr, $index:expr;) => {
+        panic!("spinlock pool slot index out of bounds")
+    };
+    (@try_lock $slots:expr, $index:expr; $n:literal $(, $rest:literal)*) => {
+        if $index == 0 {
+            $slots.0.try_lock().map($crate::PoolGuard::from)
+        } else {
+            $crate::spinlock_pool!(@try_lock $slots.1, $index - 1; $($rest),*)
+        }
+    };
+
+    (@try_lock_any $slots:expr;) => { None };
+    (@try_lock_any $slots:expr; $n:literal $(, $rest:literal)*) => {
+        match $slots.0.try_lock() {
+            Some(guard) => Some($crate::PoolGuard::from(guard)),
+            None => $crate::spinlock_pool!(@try_lock_any $slots.1; $($rest),*),
+        }
+    };
+
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident: $t:ty = $init:expr;
+        spinlocks [$($n:literal),+ $(,)?];
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            slots: $crate::spinlock_pool!(@ty $t; $($n),+),
+        }
+
+        const _: () = $crate::check_lock_budget(&[$($n),+]);
+
+        impl $name {
+            /// The number of slots.
+            $vis const LEN: usize = [$($n),+].len();
+
+            /// Creates the pool with all slots in an unlocked state.
+            #[inline]
+            $vis const fn new() -> Self {
+                Self {
+                    slots: $crate::spinlock_pool!(@new $init; $($n),+),
+                }
+            }
+
+            /// Locks the slot at `index`, spinning until it is available.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `index` is out of bounds.
+            #[inline]
+            #[track_caller]
+            $vis fn lock_slot(&self, index: usize) -> $crate::PoolGuard<$t> {
+                $crate::spinlock_pool!(@lock self.slots, index; $($n),+)
+            }
+
+            /// Tries to lock the slot at `index`, returning `None` if it is locked.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `index` is out of bounds.
+            #[inline]
+            #[track_caller]
+            $vis fn try_lock_slot(&self, index: usize) -> Option<$crate::PoolGuard<$t>> {
+                $crate::spinlock_pool!(@try_lock self.slots, index; $($n),+)
+            }
+
+            /// Locks the first slot that is not locked, returning `None` if all are locked.
+            #[inline]
+            $vis fn try_lock_any(&self) -> Option<$crate::PoolGuard<$t>> {
+                $crate::spinlock_pool!(@try_lock_any self.slots; $($n),+)
+            }
+        }
+    };
+}
+
+/// Runs a closure on the data of a locked mutex, bounding the critical section to the closure.
+///
+/// `locked!(MUTEX => |data| body)` locks `MUTEX`, runs `body` with `data` bound to `&mut T`
+/// and releases the lock right after, returning the value of `body`. This is the recommended
+/// way to access a mutex: the critical section is visible as a block, and as the guard is
+/// never named, it cannot accidentally be kept alive, e.g. by a later statement of the same
+/// function. A value borrowing `data` cannot be returned, as it would outlive the lock.
+///
+/// It works with any mutex of this crate with a `lock` method, such as
+/// [`FairSpinlockMutex`](crate::FairSpinlockMutex) or
+/// [`OrderedSpinlockMutex`](crate::OrderedSpinlockMutex). As `body` runs in a closure,
+/// `return` and `?` leave the closure, not the surrounding function.
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::{locked, SpinlockMutex};
+///
+/// struct State {
+///     x: u32,
+///     events: u32,
+/// }
+///
+/// static STATE: SpinlockMutex<7, State> = SpinlockMutex::new(State { x: 0, events: 0 });
+///
+/// locked!(STATE => |state| {
+///     state.x += 1;
+/// });
+///
+/// let events = locked!(STATE => |state| {
+///     state.events += 1;
+///     state.events
+/// });
+/// ```
+///
+/// ```compile_fail
+/// use rp_spinlockmutex::{locked, SpinlockMutex};
+/// static NAME: SpinlockMutex<7, [u8; 8]> = SpinlockMutex::new([0; 8]);
+///
+/// let name = locked!(NAME => |name| &name[..4]); // ❌ borrows the data past the lock ❌
+/// ```
+#[macro_export]
+macro_rules! locked {
+    ($mutex:expr => |$data:pat_param| $body:expr) => {
+        $crate::run_locked($mutex.lock(), |$data| $body)
+    };
+}
+
+/// Runs `f` on the data of `guard` and drops it, for [`locked!`].
+///
+/// Passing the closure to a function gives it a known parameter type, so `body` can access
+/// fields of the data without annotations.
+#[doc(hidden)]
+#[inline]
+pub fn run_locked<G: core::ops::DerefMut, R>(mut guard: G, f: impl FnOnce(&mut G::Target) -> R) -> R {
+    f(&mut guard)
+}
@@ -0,0 +1,110 @@
+use core::ops::{Deref, DerefMut};
+
+use cortex_m::interrupt::InterruptNumber;
+use rp2040_hal::pac::Interrupt;
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock with the interrupt `irq` disabled on the current core.
+    ///
+    /// If the mutex is shared with a single interrupt handler, disabling just that interrupt
+    /// line in the NVIC prevents the handler from preempting the critical section and spinning
+    /// on the lock forever, while all other interrupts stay responsive.
+    ///
+    /// The interrupt is disabled *before* the spinlock is claimed, and re-enabled only *after*
+    /// the spinlock was released on drop, so the handler never runs while the current core holds
+    /// the lock. If `irq` was already disabled when calling this, it is left disabled on drop.
+    /// If locking panics, e.g. in the checks of `core-pinning`, the interrupt is re-enabled
+    /// while unwinding.
+    ///
+    /// The NVIC is core-local: the handler of `irq` may still run on the other core, where it
+    /// simply waits for the lock as usual. On the host, the interrupt is disabled in the
+    /// `HostNvic` emulation instead.
+    ///
+    /// ```no_run
+    /// use rp2040_hal::pac::Interrupt;
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static SAMPLES: SpinlockMutex<7, [u16; 64]> = SpinlockMutex::new([0; 64]);
+    ///
+    /// // Shared with the ADC_IRQ_FIFO handler
+    /// let mut samples = SAMPLES.lock_masking(Interrupt::ADC_IRQ_FIFO);
+    /// samples.fill(0);
+    /// ```
+    #[inline]
+    pub fn lock_masking(&self, irq: Interrupt) -> MaskingGuard<N, T> {
+        // Created first, so that the interrupt is also restored if `lock` panics.
+        let unmask = Unmask::new(1 << irq.number());
+        MaskingGuard {
+            guard: self.lock(),
+            _unmask: unmask,
+        }
+    }
+}
+
+/// A guard obtained from [`SpinlockMutex::lock_masking`].
+///
+/// It behaves like a [`SpinlockMutexGuard`] and additionally keeps an interrupt disabled,
+/// which is re-enabled right after the spinlock is released on drop.
+#[must_use = "if unused the SpinlockMutex will immediately unlock"]
+pub struct MaskingGuard<const N: usize, T: ?Sized>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    // Unlike other guards, the spinlock is released first, as fields are dropped in order of
+    // declaration: a pending interrupt taken right after unmasking would otherwise spin on a
+    // lock this core still holds.
+    guard: SpinlockMutexGuard<N, T>,
+    _unmask: Unmask,
+}
+
+/// Disables interrupts on the current core until dropped, then re-enables those of them that
+/// were enabled before.
+pub(crate) struct Unmask(u32);
+
+impl Unmask {
+    /// Disables the interrupts in `mask`.
+    #[inline]
+    pub(crate) fn new(mask: u32) -> Self {
+        let enabled = crate::backend::enabled_interrupts() & mask;
+        crate::backend::disable_interrupts(mask);
+        Self(enabled)
+    }
+}
+
+impl Drop for Unmask {
+    #[inline]
+    fn drop(&mut self) {
+        if self.0 != 0 {
+            // SAFETY: These interrupts were enabled before, so re-enabling them cannot break a
+            // mask-based critical section.
+            unsafe { crate::backend::enable_interrupts(self.0) };
+        }
+    }
+}
+
+impl<const N: usize, T: ?Sized> Deref for MaskingGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<const N: usize, T: ?Sized> DerefMut for MaskingGuard<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
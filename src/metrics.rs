@@ -0,0 +1,128 @@
+use core::sync::atomic::{fence, AtomicU32, Ordering};
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::backend::{Lock, LockStrategy};
+use crate::diag::LockStats;
+use crate::SpinlockMutex;
+
+struct Counters {
+    acquisitions: AtomicU32,
+    contentions: AtomicU32,
+    max_spins: AtomicU32,
+}
+
+/// The counters of each spinlock since boot.
+///
+/// The counters of spinlock `N` are only ever written while spinlock `N` is claimed, so plain
+/// loads and stores suffice.
+#[allow(clippy::declare_interior_mutable_const)]
+static COUNTERS: [Counters; 32] = {
+    const ZERO: Counters = Counters {
+        acquisitions: AtomicU32::new(0),
+        contentions: AtomicU32::new(0),
+        max_spins: AtomicU32::new(0),
+    };
+    [ZERO; 32]
+};
+
+/// Counts an acquisition of spinlock `n`.
+///
+/// # Safety
+///
+/// Spinlock `n` must be claimed by the caller.
+#[inline]
+pub(crate) unsafe fn record_acquisition(n: usize) {
+    let acquisitions = &COUNTERS[n].acquisitions;
+    acquisitions.store(acquisitions.load(Ordering::Relaxed).wrapping_add(1), Ordering::Relaxed);
+}
+
+/// Records that claiming spinlock `n` took `spins` failed attempts.
+///
+/// # Safety
+///
+/// Spinlock `n` must be claimed by the caller.
+#[inline]
+pub(crate) unsafe fn record_spins(n: usize, spins: u32) {
+    if spins == 0 {
+        return;
+    }
+    let counters = &COUNTERS[n];
+    let contentions = counters.contentions.load(Ordering::Relaxed);
+    counters
+        .contentions
+        .store(contentions.wrapping_add(1), Ordering::Relaxed);
+    if spins > counters.max_spins.load(Ordering::Relaxed) {
+        counters.max_spins.store(spins, Ordering::Relaxed);
+    }
+}
+
+/// Returns a snapshot of the counters of spinlock `n`.
+#[inline]
+pub(crate) fn stats(n: usize) -> LockStats {
+    let counters = &COUNTERS[n];
+    LockStats {
+        acquisitions: counters.acquisitions.load(Ordering::Relaxed),
+        contentions: counters.contentions.load(Ordering::Relaxed),
+        max_spins: counters.max_spins.load(Ordering::Relaxed),
+    }
+}
+
+/// Zeroes the counters of spinlock `N`, claiming it while doing so to not interleave with an
+/// update. The claim itself is not counted.
+#[inline]
+fn reset<const N: usize>()
+where
+    Spinlock<N>: SpinlockValid,
+{
+    let lock = <Lock<N> as LockStrategy>::claim();
+    fence(Ordering::Acquire);
+    let counters = &COUNTERS[N];
+    counters.acquisitions.store(0, Ordering::Relaxed);
+    counters.contentions.store(0, Ordering::Relaxed);
+    counters.max_spins.store(0, Ordering::Relaxed);
+    fence(Ordering::Release);
+    drop(lock);
+}
+
+/// Zeroes the counters of all spinlocks, one after the other.
+pub(crate) fn reset_all() {
+    macro_rules! reset {
+        ($($n:literal)+) => {
+            $(reset::<$n>();)+
+        };
+    }
+    reset!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31);
+}
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Zeroes the [statistics](crate::diag::lock_stats) of the spinlock used by this mutex, e.g.
+    /// to measure the contention within a specific window instead of since boot.
+    ///
+    /// The spinlock is claimed while the counters are zeroed, so a concurrent update on the
+    /// other core either happens completely before or after the reset. The statistics are
+    /// global per spinlock number, so this resets them for all mutexes sharing the number.
+    ///
+    /// # Deadlock
+    ///
+    /// Calling this while holding the lock on the same core (or from an interrupt preempting
+    /// the holder) will cause a deadlock.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::diag::lock_stats;
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static MUTEX: SpinlockMutex<7, i32> = SpinlockMutex::new(0);
+    ///
+    /// MUTEX.reset_stats();
+    /// *MUTEX.lock() += 1;
+    ///
+    /// assert_eq!(lock_stats(7).acquisitions, 1);
+    /// ```
+    #[inline]
+    pub fn reset_stats(&self) {
+        reset::<N>();
+    }
+}
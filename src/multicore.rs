@@ -0,0 +1,42 @@
+/// One of the two processor cores of the rp2040.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CoreId {
+    /// Core 0, which runs after reset.
+    Core0 = 0,
+    /// Core 1, which has to be started by core 0.
+    Core1 = 1,
+}
+
+impl CoreId {
+    /// Returns the core executing this function.
+    #[inline]
+    pub fn current() -> Self {
+        match crate::backend::core() {
+            0 => CoreId::Core0,
+            _ => CoreId::Core1,
+        }
+    }
+
+    /// Makes the current thread report itself as running on this core, for emulating both
+    /// cores in host tests.
+    ///
+    /// Without this, all threads report core 0. Locks work the same on both cores, but
+    /// primitives that treat the cores differently, like [`CoreLocal`](crate::CoreLocal), can
+    /// only be tested with threads on both.
+    ///
+    /// ```
+    /// use rp_spinlockmutex::CoreId;
+    ///
+    /// std::thread::spawn(|| {
+    ///     CoreId::Core1.emulate();
+    ///     assert_eq!(CoreId::current(), CoreId::Core1);
+    /// })
+    /// .join()
+    /// .unwrap();
+    /// ```
+    #[cfg(all(not(target_arch = "arm"), feature = "std-testing"))]
+    #[inline]
+    pub fn emulate(self) {
+        crate::backend::set_core(self as u8);
+    }
+}
@@ -0,0 +1,131 @@
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::SpinlockMutex;
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A primitive integer type, usable with [`SpinlockMutex::fetch_add`] and friends.
+///
+/// The trait is sealed and implemented for all primitive integer types.
+pub trait Numeric: Copy + private::Sealed {
+    #[doc(hidden)]
+    const ONE: Self;
+    #[doc(hidden)]
+    fn wrapping_add(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn wrapping_sub(self, rhs: Self) -> Self;
+    #[doc(hidden)]
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    #[doc(hidden)]
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+}
+
+macro_rules! impl_numeric {
+    ($($t:ty)+) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl Numeric for $t {
+                const ONE: Self = 1;
+
+                #[inline]
+                fn wrapping_add(self, rhs: Self) -> Self {
+                    <$t>::wrapping_add(self, rhs)
+                }
+
+                #[inline]
+                fn wrapping_sub(self, rhs: Self) -> Self {
+                    <$t>::wrapping_sub(self, rhs)
+                }
+
+                #[inline]
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                #[inline]
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+            }
+        )+
+    };
+}
+
+impl_numeric!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+impl<const N: usize, T: Numeric> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Adds `v` to the protected value, wrapping around on overflow, and returns the previous
+    /// value.
+    ///
+    /// Like the `fetch_add` of the atomic integers, which the Cortex-M0+ lacks, the
+    /// read-modify-write happens in a single, minimal critical section.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static EVENTS: SpinlockMutex<7, u32> = SpinlockMutex::new(0);
+    ///
+    /// assert_eq!(EVENTS.fetch_add(3), 0);
+    /// assert_eq!(EVENTS.fetch_sub(1), 3);
+    /// assert_eq!(EVENTS.increment(), 2);
+    /// assert_eq!(*EVENTS.lock(), 3);
+    /// ```
+    #[inline]
+    pub fn fetch_add(&self, v: T) -> T {
+        let mut guard = self.lock();
+        let previous = *guard;
+        *guard = previous.wrapping_add(v);
+        previous
+    }
+
+    /// Subtracts `v` from the protected value, wrapping around on overflow, and returns the
+    /// previous value.
+    #[inline]
+    pub fn fetch_sub(&self, v: T) -> T {
+        let mut guard = self.lock();
+        let previous = *guard;
+        *guard = previous.wrapping_sub(v);
+        previous
+    }
+
+    /// Adds one to the protected value, wrapping around on overflow, and returns the previous
+    /// value.
+    #[inline]
+    pub fn increment(&self) -> T {
+        self.fetch_add(T::ONE)
+    }
+
+    /// Adds `v` to the protected value and returns the previous value, or leaves it unchanged
+    /// and returns `None` if the addition would overflow.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static FREE_SLOTS: SpinlockMutex<7, u8> = SpinlockMutex::new(254);
+    ///
+    /// assert_eq!(FREE_SLOTS.checked_fetch_add(1), Some(254));
+    /// assert_eq!(FREE_SLOTS.checked_fetch_add(1), None);
+    /// assert_eq!(*FREE_SLOTS.lock(), 255);
+    /// ```
+    #[inline]
+    pub fn checked_fetch_add(&self, v: T) -> Option<T> {
+        let mut guard = self.lock();
+        let previous = *guard;
+        *guard = previous.checked_add(v)?;
+        Some(previous)
+    }
+
+    /// Subtracts `v` from the protected value and returns the previous value, or leaves it
+    /// unchanged and returns `None` if the subtraction would overflow.
+    #[inline]
+    pub fn checked_fetch_sub(&self, v: T) -> Option<T> {
+        let mut guard = self.lock();
+        let previous = *guard;
+        *guard = previous.checked_sub(v)?;
+        Some(previous)
+    }
+}
@@ -0,0 +1,183 @@
+use core::ops::{Deref, DerefMut};
+#[cfg(debug_assertions)]
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+/// The orders of the locks held by each core, as a mask with bit `ORDER` set for each held lock.
+///
+/// Each core only accesses its own mask. An interrupt handler preempting a read-modify-write
+/// releases the locks it acquires before returning, so it leaves the mask as it found it.
+#[cfg(debug_assertions)]
+static HELD: [AtomicU32; 2] = [AtomicU32::new(0), AtomicU32::new(0)];
+
+/// A [`SpinlockMutex`] with a position `ORDER` in the lock hierarchy of the application.
+///
+/// Two cores locking the same two mutexes in opposite order can deadlock each other. This is
+/// avoided by assigning every mutex an order and only ever locking mutexes in increasing
+/// order. In debug builds, locking a mutex while the current core already holds one of the
+/// same or a higher order panics, which catches such an inversion on the first run of the
+/// offending code path, instead of only once the two cores happen to interleave badly.
+///
+/// `ORDER` must be less than 32. Interrupt handlers are tracked as part of the core they run
+/// on, as they can deadlock with the other core just as well. In release builds, this behaves
+/// exactly like a regular `SpinlockMutex`.
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::OrderedSpinlockMutex;
+/// static CONFIG: OrderedSpinlockMutex<3, 0, u32> = OrderedSpinlockMutex::new(0);
+/// static STATE: OrderedSpinlockMutex<4, 1, u32> = OrderedSpinlockMutex::new(0);
+///
+/// let config = CONFIG.lock();
+/// *STATE.lock() = *config; // ✅ STATE is of a higher order
+///
+/// drop(config);
+/// let state = STATE.lock();
+/// let config = CONFIG.lock(); // ❌ panics in debug builds ❌
+/// ```
+pub struct OrderedSpinlockMutex<const N: usize, const ORDER: u32, T: ?Sized>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    mutex: SpinlockMutex<N, T>,
+}
+
+impl<const N: usize, const ORDER: u32, T> OrderedSpinlockMutex<N, ORDER, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates a new hardware based spinlock mutex in an unlocked state ready for use.
+    #[inline]
+    pub const fn new(data: T) -> Self {
+        Self {
+            mutex: SpinlockMutex::new(data),
+        }
+    }
+}
+
+impl<const N: usize, const ORDER: u32, T: ?Sized> OrderedSpinlockMutex<N, ORDER, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    const VALID_ORDER: () = assert!(ORDER < 32, "the order of an OrderedSpinlockMutex must be less than 32");
+
+    /// Acquires the mutex lock, blocking the current thread until the lock is available.
+    ///
+    /// See [`SpinlockMutex::lock`] for details.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the current core holds an `OrderedSpinlockMutex` of the same
+    /// or a higher order.
+    #[inline]
+    #[track_caller]
+    pub fn lock(&self) -> OrderedGuard<N, ORDER, T> {
+        self.check_order();
+        self.acquired(self.mutex.lock())
+    }
+
+    /// Attempts to acquire this lock without spinning.
+    ///
+    /// See [`SpinlockMutex::try_lock`] for details.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if the current core holds an `OrderedSpinlockMutex` of the same
+    /// or a higher order, even if the lock is available.
+    #[inline]
+    #[track_caller]
+    pub fn try_lock(&self) -> Option<OrderedGuard<N, ORDER, T>> {
+        self.check_order();
+        self.mutex.try_lock().map(|guard| self.acquired(guard))
+    }
+
+    /// Returns the number of the hardware spinlock used by this mutex.
+    #[inline]
+    pub const fn spinlock_number(&self) -> usize {
+        N
+    }
+
+    /// Panics in debug builds if the current core holds a lock of the same or a higher order.
+    #[inline(always)]
+    #[track_caller]
+    fn check_order(&self) {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_ORDER;
+        #[cfg(debug_assertions)]
+        {
+            let held = HELD[crate::backend::core() as usize].load(Ordering::Relaxed);
+            if held >> ORDER != 0 {
+                panic!(
+                    "lock order violation: SpinlockMutex<{}, _>{} of order {} locked while holding order {}",
+                    N,
+                    crate::diag::NameSuffix(N),
+                    ORDER,
+                    31 - held.leading_zeros()
+                );
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn acquired(&self, guard: SpinlockMutexGuard<N, T>) -> OrderedGuard<N, ORDER, T> {
+        #[cfg(debug_assertions)]
+        {
+            let held = &HELD[crate::backend::core() as usize];
+            held.store(held.load(Ordering::Relaxed) | 1 << ORDER, Ordering::Relaxed);
+        }
+        OrderedGuard { guard }
+    }
+}
+
+/// A guard obtained from [`OrderedSpinlockMutex::lock`].
+///
+/// It behaves like a [`SpinlockMutexGuard`] and additionally tracks the order of the held lock
+/// in debug builds.
+#[must_use = "if unused the SpinlockMutex will immediately unlock"]
+pub struct OrderedGuard<const N: usize, const ORDER: u32, T: ?Sized>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    guard: SpinlockMutexGuard<N, T>,
+}
+
+impl<const N: usize, const ORDER: u32, T: ?Sized> Drop for OrderedGuard<N, ORDER, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        // `guard` releases the spinlock after this, as it is dropped last.
+        #[cfg(debug_assertions)]
+        {
+            let held = &HELD[crate::backend::core() as usize];
+            held.store(held.load(Ordering::Relaxed) & !(1 << ORDER), Ordering::Relaxed);
+        }
+    }
+}
+
+impl<const N: usize, const ORDER: u32, T: ?Sized> Deref for OrderedGuard<N, ORDER, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<const N: usize, const ORDER: u32, T: ?Sized> DerefMut for OrderedGuard<N, ORDER, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
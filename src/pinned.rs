@@ -0,0 +1,86 @@
+use core::pin::Pin;
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+/// A mutex whose protected data is pinned, i.e. never moves once the mutex is pinned.
+///
+/// A regular [`SpinlockMutex`] hands out `&mut T`, which allows moving the data out, e.g. with
+/// [`mem::replace`](core::mem::replace). This mutex instead only gives out `Pin<&mut T>` once
+/// the mutex itself is pinned, which makes it suitable for address-sensitive `!Unpin` data like
+/// intrusive lists shared across cores. For `T: Unpin` it offers nothing over a `SpinlockMutex`.
+///
+/// A `static` mutex can be pinned with [`Pin::static_ref`].
+///
+/// # Example
+///
+/// ```no_run
+/// use core::marker::PhantomPinned;
+/// use core::pin::Pin;
+/// use rp_spinlockmutex::PinnedSpinlockMutex;
+///
+/// struct Node {
+///     value: u32,
+///     _pin: PhantomPinned,
+/// }
+///
+/// static NODE: PinnedSpinlockMutex<7, Node> = PinnedSpinlockMutex::new(Node { value: 0, _pin: PhantomPinned });
+///
+/// let mut node = Pin::static_ref(&NODE).lock();
+/// // SAFETY: `value` is not structurally pinned, so it may be accessed mutably.
+/// unsafe { node.as_mut().get_unchecked_mut().value += 1 };
+/// ```
+pub struct PinnedSpinlockMutex<const N: usize, T: ?Sized>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    mutex: SpinlockMutex<N, T>,
+}
+
+impl<const N: usize, T> PinnedSpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates a new hardware based spinlock mutex in an unlocked state ready for use.
+    #[inline]
+    pub const fn new(data: T) -> Self {
+        Self {
+            mutex: SpinlockMutex::new(data),
+        }
+    }
+}
+
+impl<const N: usize, T: ?Sized> PinnedSpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock, blocking the current thread until the lock is available.
+    ///
+    /// See [`SpinlockMutex::lock`] for details.
+    #[inline]
+    #[track_caller]
+    pub fn lock(self: Pin<&Self>) -> Pin<SpinlockMutexGuard<N, T>> {
+        // SAFETY: The data is stored inline in the pinned mutex, so it does not move until the
+        // mutex is dropped, which also drops the data in place. This type never hands out an
+        // unpinned `&mut T`, so the data cannot be moved through the guard either.
+        unsafe { Pin::new_unchecked(self.get_ref().mutex.lock()) }
+    }
+
+    /// Attempts to acquire the mutex lock, returning `None` if it is held elsewhere.
+    #[inline]
+    #[track_caller]
+    pub fn try_lock(self: Pin<&Self>) -> Option<Pin<SpinlockMutexGuard<N, T>>> {
+        // SAFETY: See `lock`.
+        self.get_ref()
+            .mutex
+            .try_lock()
+            .map(|guard| unsafe { Pin::new_unchecked(guard) })
+    }
+
+    /// Returns the number of the hardware spinlock used by this mutex.
+    #[inline]
+    pub const fn spinlock_number(&self) -> usize {
+        N
+    }
+}
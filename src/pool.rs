@@ -0,0 +1,89 @@
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::Ordering;
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::backend::{fence, Lock, LockStrategy};
+use crate::SpinlockMutexGuard;
+
+/// A guard of a slot of a [`spinlock_pool!`](crate::spinlock_pool), for any spinlock number.
+///
+/// The slots of a pool use different spinlock numbers, so their [`SpinlockMutexGuard`]s have
+/// different types. This guard erases the spinlock number into a runtime value, at the cost
+/// of an indirect call when dropped. It can also be created from any `SpinlockMutexGuard`
+/// with [`From`].
+#[must_use = "if unused the spinlock will immediately unlock"]
+pub struct PoolGuard<T: ?Sized> {
+    data: *mut T,
+    spinlock_number: usize,
+    release: unsafe fn(),
+}
+
+unsafe impl<T: ?Sized + Send> Send for PoolGuard<T> {}
+unsafe impl<T: ?Sized + Sync> Sync for PoolGuard<T> {}
+
+impl<T: ?Sized> PoolGuard<T> {
+    /// Returns the number of the hardware spinlock held by this guard.
+    #[inline]
+    pub fn spinlock_number(&self) -> usize {
+        self.spinlock_number
+    }
+}
+
+/// Releases spinlock `N` like dropping a [`SpinlockMutexGuard`] does.
+///
+/// # Safety
+///
+/// Spinlock `N` must be claimed by a guard that was forgotten.
+unsafe fn release<const N: usize>()
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[cfg(feature = "trace")]
+    crate::trace::emit(N, crate::LockEvent::Released);
+    fence(Ordering::Release);
+    <Lock<N> as LockStrategy>::release();
+}
+
+impl<const N: usize, T: ?Sized> From<SpinlockMutexGuard<N, T>> for PoolGuard<T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn from(guard: SpinlockMutexGuard<N, T>) -> Self {
+        let data = guard.data;
+        // The spinlock stays claimed until `release` is called when this guard is dropped.
+        core::mem::forget(guard);
+        Self {
+            data,
+            spinlock_number: N,
+            release: release::<N>,
+        }
+    }
+}
+
+impl<T: ?Sized> Drop for PoolGuard<T> {
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: The spinlock was claimed by the guard this one was created from.
+        unsafe { (self.release)() };
+    }
+}
+
+impl<T: ?Sized> Deref for PoolGuard<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: This guard holds the spinlock protecting the data.
+        unsafe { &*self.data }
+    }
+}
+
+impl<T: ?Sized> DerefMut for PoolGuard<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: This guard holds the spinlock protecting the data.
+        unsafe { &mut *self.data }
+    }
+}
@@ -0,0 +1,84 @@
+use core::ops::{Deref, DerefMut};
+
+use embedded_hal::digital::v2::OutputPin;
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock and drives `pin` high until the guard is dropped, for measuring
+    /// how long the lock is held with a logic analyzer or scope.
+    ///
+    /// The pin is set high right after the spinlock is claimed, and low right before it is
+    /// released, so the pulse covers exactly the critical section (plus the time to toggle the
+    /// pin). Time spent waiting for the lock is not included. Errors of the pin are ignored, so
+    /// a failing probe does not disturb the code under test.
+    ///
+    /// ```no_run
+    /// use rp2040_hal::{gpio::Pins, pac, Sio};
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static SAMPLES: SpinlockMutex<7, [u16; 64]> = SpinlockMutex::new([0; 64]);
+    ///
+    /// let mut pac = pac::Peripherals::take().unwrap();
+    /// let sio = Sio::new(pac.SIO);
+    /// let pins = Pins::new(pac.IO_BANK0, pac.PADS_BANK0, sio.gpio_bank0, &mut pac.RESETS);
+    /// let mut probe = pins.gpio2.into_push_pull_output();
+    ///
+    /// let mut samples = SAMPLES.lock_probe(&mut probe);
+    /// samples.fill(0);
+    /// ```
+    #[inline]
+    pub fn lock_probe<'a, P: OutputPin>(&'a self, pin: &'a mut P) -> ProbeGuard<'a, N, T, P> {
+        let guard = self.lock();
+        let _ = pin.set_high();
+        ProbeGuard { guard, pin }
+    }
+}
+
+/// A guard obtained from [`SpinlockMutex::lock_probe`].
+///
+/// It behaves like a [`SpinlockMutexGuard`] and additionally holds the probe pin, which is
+/// driven low right before the spinlock is released on drop.
+#[must_use = "if unused the SpinlockMutex will immediately unlock"]
+pub struct ProbeGuard<'a, const N: usize, T: ?Sized, P: OutputPin>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    guard: SpinlockMutexGuard<N, T>,
+    pin: &'a mut P,
+}
+
+impl<const N: usize, T: ?Sized, P: OutputPin> Drop for ProbeGuard<'_, N, T, P>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        let _ = self.pin.set_low();
+    }
+}
+
+impl<const N: usize, T: ?Sized, P: OutputPin> Deref for ProbeGuard<'_, N, T, P>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<const N: usize, T: ?Sized, P: OutputPin> DerefMut for ProbeGuard<'_, N, T, P>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
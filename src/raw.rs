@@ -0,0 +1,69 @@
+use core::sync::atomic::Ordering;
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::backend::{fence, Lock, LockStrategy};
+
+/// A [`lock_api::Mutex`] protected by hardware spinlock `N`.
+///
+/// This gives access to the features of `lock_api`, like mapped guards or guards holding an
+/// `Arc` of the mutex, on top of the same hardware spinlocks as [`SpinlockMutex`](crate::SpinlockMutex).
+///
+/// ```no_run
+/// use rp_spinlockmutex::{LockApiMutex, LockApiMutexGuard};
+/// static POSITION: LockApiMutex<7, (i32, i32)> = LockApiMutex::new((0, 0));
+///
+/// let mut x = LockApiMutexGuard::map(POSITION.lock(), |(x, _)| x);
+/// *x += 1;
+/// ```
+pub type LockApiMutex<const N: usize, T> = lock_api::Mutex<RawSpinlock<N>, T>;
+
+/// A guard of a [`LockApiMutex`].
+pub type LockApiMutexGuard<'a, const N: usize, T> = lock_api::MutexGuard<'a, RawSpinlock<N>, T>;
+
+/// A zero-sized [`lock_api::RawMutex`] backed by hardware spinlock `N`.
+///
+/// Like all hardware spinlocks, it is global: all `RawSpinlock<N>` (and `SpinlockMutex<N, _>`)
+/// contend for the same lock.
+pub struct RawSpinlock<const N: usize>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    _private: (),
+}
+
+unsafe impl<const N: usize> lock_api::RawMutex for RawSpinlock<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    const INIT: Self = Self { _private: () };
+
+    // Any core may release a hardware spinlock.
+    type GuardMarker = lock_api::GuardSend;
+
+    #[inline]
+    fn lock(&self) {
+        core::mem::forget(<Lock<N> as LockStrategy>::claim());
+        fence(Ordering::Acquire);
+    }
+
+    #[inline]
+    fn try_lock(&self) -> bool {
+        let claimed = <Lock<N> as LockStrategy>::try_claim().map(core::mem::forget).is_some();
+        if claimed {
+            fence(Ordering::Acquire);
+        }
+        claimed
+    }
+
+    #[inline]
+    unsafe fn unlock(&self) {
+        fence(Ordering::Release);
+        <Lock<N> as LockStrategy>::release();
+    }
+
+    #[inline]
+    fn is_locked(&self) -> bool {
+        crate::backend::locked_mask() & (1 << N) != 0
+    }
+}
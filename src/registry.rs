@@ -0,0 +1,72 @@
+//! Runtime detection of lock numbers shared by unrelated mutexes (`registry` feature).
+
+use core::cell::UnsafeCell;
+
+/// The mutex registered for a spinlock number: the name of its protected type, and the address
+/// range of its data to recognize views of it with another type.
+#[derive(Clone, Copy)]
+struct Owner {
+    type_name: &'static str,
+    start: usize,
+    end: usize,
+}
+
+/// The owner of each spinlock number, i.e. the first mutex locked with it.
+struct Slot(UnsafeCell<Option<Owner>>);
+
+// SAFETY: Slot `N` is only accessed while spinlock `N` is claimed.
+unsafe impl Sync for Slot {}
+
+#[allow(clippy::declare_interior_mutable_const)]
+static SLOTS: [Slot; 32] = {
+    const EMPTY: Slot = Slot(UnsafeCell::new(None));
+    [EMPTY; 32]
+};
+
+/// Records that spinlock `N` protects the `T` at `data`, panicking if it was already used for
+/// another type.
+///
+/// Any number of mutexes of the same type may share the spinlock, e.g. the elements of an
+/// array. A mutex of another type is only accepted if its data overlaps the registered one,
+/// as a view of the same mutex with an unsized type (e.g. `dyn FnMut()`) has another type name.
+///
+/// # Safety
+///
+/// Spinlock `N` must be claimed by the caller, and `data` must point to a valid `T`.
+#[inline]
+pub(crate) unsafe fn register<const N: usize, T: ?Sized>(data: *const T) {
+    let size = core::mem::size_of_val(&*data);
+    register_range::<N>(core::any::type_name::<T>(), data as *const () as usize, size);
+}
+
+/// Records that spinlock `N` protects the `size` bytes of `type_name`s at `start`, which may be
+/// several mutexes locked together, see [`register`].
+///
+/// # Safety
+///
+/// Spinlock `N` must be claimed by the caller.
+#[inline]
+pub(crate) unsafe fn register_range<const N: usize>(type_name: &'static str, start: usize, size: usize) {
+    // Zero-sized data still occupies its address.
+    let end = start + size.max(1);
+    let slot = &mut *SLOTS[N].0.get();
+    match *slot {
+        None => *slot = Some(Owner { type_name, start, end }),
+        // Growing the range over neighbouring mutexes of the same type, e.g. the elements of an
+        // array, lets views of any of them through.
+        Some(ref mut owner) if owner.type_name == type_name => {
+            if start <= owner.end && owner.start <= end {
+                owner.start = owner.start.min(start);
+                owner.end = owner.end.max(end);
+            }
+        }
+        Some(owner) if start < owner.end && owner.start < end => {}
+        Some(owner) => panic!(
+            "spinlock {}{} is used by mutexes for both `{}` and `{}`",
+            N,
+            crate::diag::NameSuffix(N),
+            owner.type_name,
+            type_name
+        ),
+    }
+}
@@ -0,0 +1,73 @@
+use core::mem::ManuallyDrop;
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::SpinlockMutex;
+
+impl<const N: usize, T> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Temporarily replaces the protected value with `temp`, restoring the previous value when the
+    /// returned guard is dropped.
+    ///
+    /// Unlike a [`SpinlockMutexGuard`](crate::SpinlockMutexGuard), the returned guard does
+    /// **not** hold the lock. Setting and restoring are two separate critical sections, so other
+    /// code (including the other core) may lock the mutex in between and will observe `temp`,
+    /// or modify it. Any such modification is discarded on restoration.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static VERBOSE: SpinlockMutex<7, bool> = SpinlockMutex::new(false);
+    ///
+    /// {
+    ///     let _verbose = VERBOSE.scoped_set(true);
+    ///     assert!(*VERBOSE.lock());
+    /// }
+    ///
+    /// assert!(!*VERBOSE.lock());
+    /// ```
+    #[inline]
+    pub fn scoped_set(&self, temp: T) -> ScopedSetGuard<'_, N, T> {
+        let previous = core::mem::replace(&mut *self.lock(), temp);
+        ScopedSetGuard {
+            mutex: self,
+            previous: ManuallyDrop::new(previous),
+        }
+    }
+}
+
+/// A guard obtained from [`SpinlockMutex::scoped_set`], holding the previous value of the mutex.
+/// If this guard is dropped, the previous value is restored.
+#[must_use = "if unused the previous value will immediately be restored"]
+pub struct ScopedSetGuard<'a, const N: usize, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    mutex: &'a SpinlockMutex<N, T>,
+    previous: ManuallyDrop<T>,
+}
+
+impl<const N: usize, T> ScopedSetGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Returns the value that will be restored when this guard is dropped.
+    #[inline]
+    pub fn previous(&self) -> &T {
+        &self.previous
+    }
+}
+
+impl<const N: usize, T> Drop for ScopedSetGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        // SAFETY: `previous` is never used again after this.
+        let previous = unsafe { ManuallyDrop::take(&mut self.previous) };
+        // The temporary value is dropped after the lock was released.
+        let _temp = core::mem::replace(&mut *self.mutex.lock(), previous);
+    }
+}
@@ -0,0 +1,108 @@
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::SpinlockMutex;
+
+/// A counting semaphore whose permit count is protected by the rp2040 hardware spinlock.
+///
+/// This can be used to bound the number of in-flight operations across both cores. The
+/// spinlock is only claimed while the count is inspected or changed, not while a permit is held.
+/// Permits are only returned by dropping a [`Permit`], so the count never exceeds the number
+/// of permits the semaphore was created with.
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::SpinlockSemaphore;
+/// static IN_FLIGHT: SpinlockSemaphore<7> = SpinlockSemaphore::new(2);
+///
+/// let first = IN_FLIGHT.acquire();
+/// let second = IN_FLIGHT.acquire();
+/// assert!(IN_FLIGHT.try_acquire().is_none());
+///
+/// drop(first);
+/// assert_eq!(IN_FLIGHT.available(), 1);
+/// ```
+pub struct SpinlockSemaphore<const N: usize>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    permits: SpinlockMutex<N, usize>,
+}
+
+impl<const N: usize> SpinlockSemaphore<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates a new hardware based spinlock semaphore with the given number of permits.
+    #[inline]
+    pub const fn new(permits: usize) -> Self {
+        Self {
+            permits: SpinlockMutex::new(permits),
+        }
+    }
+
+    /// Acquires a permit, spinning until one is available.
+    ///
+    /// # Deadlock
+    ///
+    /// Holding all permits and acquiring another one on the same core (or from an interrupt
+    /// preempting the holder) will cause a deadlock.
+    #[inline]
+    pub fn acquire(&self) -> Permit<'_, N> {
+        loop {
+            if let Some(permit) = self.try_acquire() {
+                break permit;
+            }
+        }
+    }
+
+    /// Tries to acquire a permit, returning `None` if none is available.
+    #[inline]
+    pub fn try_acquire(&self) -> Option<Permit<'_, N>> {
+        let mut permits = self.permits.lock();
+        if *permits == 0 {
+            return None;
+        }
+        *permits -= 1;
+        Some(Permit { semaphore: self })
+    }
+
+    /// Returns the number of currently available permits.
+    ///
+    /// The value may already be outdated when this function returns.
+    #[inline]
+    pub fn available(&self) -> usize {
+        *self.permits.lock()
+    }
+}
+
+/// A permit of a [`SpinlockSemaphore`]. If this permit is dropped, it is returned to the semaphore.
+/// It can also be returned manually with [`Permit::release`].
+#[must_use = "if unused the permit will immediately be released"]
+pub struct Permit<'a, const N: usize>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    semaphore: &'a SpinlockSemaphore<N>,
+}
+
+impl<const N: usize> Permit<'_, N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Returns the permit to its semaphore.
+    #[inline]
+    pub fn release(self) {
+        core::mem::drop(self);
+    }
+}
+
+impl<const N: usize> Drop for Permit<'_, N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        *self.semaphore.permits.lock() += 1;
+    }
+}
@@ -0,0 +1,129 @@
+use core::sync::atomic::{fence, AtomicU32, Ordering};
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::SpinlockMutex;
+
+/// A sequence lock for `Copy` values, where readers never block the writer.
+///
+/// Writers claim spinlock `N`, so they are serialized like the users of a [`SpinlockMutex`].
+/// Readers do not claim it at all: they copy the value and retry if a write happened in the
+/// meantime. This suits read-mostly data like a timestamp that one core updates and both cores
+/// read frequently, as readers neither contend for the spinlock nor delay the writer.
+///
+/// # Tearing
+///
+/// A sequence counter is incremented to an odd value before every write and to the next even
+/// value after it. A reader notes the counter, copies the value, and reads the counter again.
+/// If both reads returned the same even value, no write overlapped the copy, so it is not torn.
+/// Otherwise the copy is discarded and the read retried. This is why `T` must be `Copy`: a torn
+/// copy may be an invalid value, so it is only read bytewise and never used or dropped.
+///
+/// # Deadlock
+///
+/// A reader spins while a write is in progress. Reading from an interrupt handler that
+/// preempted a write on the same core therefore never returns, and neither does writing, as
+/// with [`SpinlockMutex::lock`]. Reading from the other core is always fine.
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::SpinlockSeqLock;
+/// static LAST_TICK: SpinlockSeqLock<7, (u32, u32)> = SpinlockSeqLock::new((0, 0));
+/// # fn run_on_core1(f: impl FnOnce()) {}
+///
+/// run_on_core1(|| loop {
+///     LAST_TICK.write(|(seconds, micros)| {
+///         *micros += 100;
+///         if *micros == 1_000_000 {
+///             *seconds += 1;
+///             *micros = 0;
+///         }
+///     });
+/// });
+///
+/// // Never observes a new `seconds` with an old `micros`
+/// let (seconds, micros) = LAST_TICK.read();
+/// ```
+pub struct SpinlockSeqLock<const N: usize, T: Copy>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Odd while a write is in progress, only written while spinlock `N` is claimed.
+    sequence: AtomicU32,
+    mutex: SpinlockMutex<N, T>,
+}
+
+impl<const N: usize, T: Copy> SpinlockSeqLock<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates a new sequence lock containing the given value.
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Self {
+            sequence: AtomicU32::new(0),
+            mutex: SpinlockMutex::new(value),
+        }
+    }
+
+    /// Returns a copy of the value, without claiming the spinlock.
+    ///
+    /// Spins while a write is in progress and retries if a write overlapped the copy.
+    #[inline]
+    pub fn read(&self) -> T {
+        loop {
+            let before = self.sequence.load(Ordering::Acquire);
+            if before % 2 == 1 {
+                core::hint::spin_loop();
+                continue;
+            }
+            // SAFETY: The pointer is valid and aligned. A concurrent write may tear the copy,
+            //         which is then discarded below. As `T` is `Copy`, it has no drop glue, and
+            //         a volatile read keeps the compiler from assuming the memory is unchanged.
+            let value = unsafe { core::ptr::read_volatile(self.mutex.data.get()) };
+            // Orders the copy before the second read of the sequence, pairing with the fence
+            // in `write`.
+            fence(Ordering::Acquire);
+            if self.sequence.load(Ordering::Relaxed) == before {
+                break value;
+            }
+        }
+    }
+
+    /// Locks spinlock `N` and runs `f` on the value, making concurrent readers retry.
+    ///
+    /// `f` should be short, as readers spin for as long as it runs. If `f` panics, the write
+    /// still ends, and readers see the value as `f` left it.
+    #[inline]
+    #[track_caller]
+    pub fn write(&self, f: impl FnOnce(&mut T)) {
+        let mut guard = self.mutex.lock();
+        // Only written while the spinlock is claimed, so a load and a store suffice.
+        let sequence = self.sequence.load(Ordering::Relaxed);
+        self.sequence.store(sequence.wrapping_add(1), Ordering::Relaxed);
+        // Dropped before `guard`, so the sequence is even again before the spinlock is released.
+        let _end = EndWrite(&self.sequence, sequence.wrapping_add(2));
+        // Orders the odd sequence before the writes to the value, pairing with the fence in
+        // `read`.
+        fence(Ordering::Release);
+        f(&mut guard);
+    }
+
+    /// Returns the number of the hardware spinlock used by this sequence lock.
+    #[inline]
+    pub const fn spinlock_number(&self) -> usize {
+        N
+    }
+}
+
+/// Stores the even sequence ending a write on drop, also if the write panics, making the
+/// writes to the value visible to readers that see it.
+struct EndWrite<'a>(&'a AtomicU32, u32);
+
+impl Drop for EndWrite<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.0.store(self.1, Ordering::Release);
+    }
+}
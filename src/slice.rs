@@ -0,0 +1,176 @@
+use core::marker::PhantomData;
+use core::ops::{Index, IndexMut};
+use core::sync::atomic::Ordering;
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::backend::{fence, Lock};
+use crate::SpinlockMutex;
+
+impl<const N: usize, T> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the lock of all `mutexes` at once, claiming spinlock `N` only a single time.
+    ///
+    /// All mutexes with the same spinlock number are protected by the same hardware lock, so
+    /// locking them one after the other would claim the same lock again and again, or deadlock
+    /// if the guards are kept. The returned guard instead gives access to all elements under
+    /// one claim.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static CHANNELS: [SpinlockMutex<7, u16>; 4] = [
+    ///     SpinlockMutex::new(0),
+    ///     SpinlockMutex::new(0),
+    ///     SpinlockMutex::new(0),
+    ///     SpinlockMutex::new(0),
+    /// ];
+    ///
+    /// let mut channels = SpinlockMutex::lock_all(&CHANNELS);
+    /// channels[0] = 100;
+    /// for channel in channels.iter_mut() {
+    ///     *channel /= 2;
+    /// }
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lock_all(mutexes: &[Self]) -> SliceGuard<'_, N, T> {
+        mutexes.iter().for_each(Self::check_core);
+        let lock = Self::claim();
+        // See `SpinlockMutexGuard::new`.
+        fence(Ordering::Acquire);
+        #[cfg(all(feature = "registry", debug_assertions))]
+        // SAFETY: `lock` proves that spinlock `N` is claimed.
+        unsafe {
+            crate::registry::register_range::<N>(
+                core::any::type_name::<T>(),
+                mutexes.as_ptr() as usize,
+                core::mem::size_of_val(mutexes),
+            )
+        };
+        #[cfg(feature = "metrics")]
+        // SAFETY: `lock` proves that spinlock `N` is claimed.
+        unsafe {
+            crate::metrics::record_acquisition(N)
+        };
+        #[cfg(feature = "trace")]
+        crate::trace::emit(N, crate::LockEvent::Acquired);
+        SliceGuard {
+            _lock: lock,
+            mutexes,
+            _data: PhantomData,
+        }
+    }
+}
+
+/// A guard obtained from [`SpinlockMutex::lock_all`], giving access to the data of a slice of
+/// mutexes sharing one spinlock.
+#[must_use = "if unused the SpinlockMutex will immediately unlock"]
+pub struct SliceGuard<'a, const N: usize, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    _lock: Lock<N>,
+    mutexes: &'a [SpinlockMutex<N, T>],
+    /// The guard lends out `&mut T`, so it may only be shared across threads if `T: Sync`.
+    _data: PhantomData<&'a mut T>,
+}
+
+impl<const N: usize, T> SliceGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Returns the number of mutexes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.mutexes.len()
+    }
+
+    /// Returns `true` if there are no mutexes.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.mutexes.is_empty()
+    }
+
+    /// Returns a reference to the data of the mutex at `index`, or `None` if out of bounds.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        // SAFETY: The spinlock protecting all the mutexes is claimed by this guard, and the
+        //         borrow of `self` prevents mutable access for the lifetime of the reference.
+        self.mutexes.get(index).map(|mutex| unsafe { &*mutex.data.get() })
+    }
+
+    /// Returns a mutable reference to the data of the mutex at `index`, or `None` if out of
+    /// bounds.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        // SAFETY: As for `get`, with the borrow of `self` preventing any other access.
+        self.mutexes.get(index).map(|mutex| unsafe { &mut *mutex.data.get() })
+    }
+
+    /// Returns an iterator over the data of all mutexes.
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        // SAFETY: As for `get`.
+        self.mutexes.iter().map(|mutex| unsafe { &*mutex.data.get() })
+    }
+
+    /// Returns an iterator over mutable references to the data of all mutexes.
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        // SAFETY: As for `get_mut`. Each mutex has its own data, so the references are disjoint.
+        self.mutexes.iter().map(|mutex| unsafe { &mut *mutex.data.get() })
+    }
+
+    /// Returns the number of the hardware spinlock held by this guard.
+    #[inline]
+    pub const fn spinlock_number(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize, T> Drop for SliceGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        #[cfg(feature = "trace")]
+        crate::trace::emit(N, crate::LockEvent::Released);
+        // See `SpinlockMutexGuard::drop`.
+        fence(Ordering::Release);
+    }
+}
+
+impl<const N: usize, T> Index<usize> for SliceGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Output = T;
+
+    #[inline]
+    #[track_caller]
+    fn index(&self, index: usize) -> &T {
+        let len = self.len();
+        match self.get(index) {
+            Some(data) => data,
+            None => panic!("index {} out of bounds for {} mutexes", index, len),
+        }
+    }
+}
+
+impl<const N: usize, T> IndexMut<usize> for SliceGuard<'_, N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    #[track_caller]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len();
+        match self.get_mut(index) {
+            Some(data) => data,
+            None => panic!("index {} out of bounds for {} mutexes", index, len),
+        }
+    }
+}
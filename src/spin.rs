@@ -0,0 +1,128 @@
+//! Contention strategies for [`SpinlockMutex::lock_with_strategy`].
+
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+/// What [`SpinlockMutex::lock_with_strategy`] does after a failed attempt to claim the spinlock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SpinAction {
+    /// Try to claim the spinlock again.
+    Retry,
+    /// Give up acquiring the lock.
+    Abort,
+}
+
+/// Controls the contention behavior of [`SpinlockMutex::lock_with_strategy`].
+///
+/// This crate provides [`Busy`], [`ExponentialBackoff`], [`BoundedRetries`] and, on the
+/// rp2040, [`Wfe`]. Custom strategies can combine them, or wire in timeouts and other stop
+/// conditions.
+pub trait SpinStrategy {
+    /// Called after the `attempt`-th failed attempt to claim the spinlock, counting from 0.
+    ///
+    /// The strategy may wait before returning, e.g. to reduce bus contention.
+    fn on_spin(&mut self, attempt: u32) -> SpinAction;
+}
+
+/// Retries immediately, forever. This is what [`SpinlockMutex::lock`] does.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Busy;
+
+impl SpinStrategy for Busy {
+    #[inline]
+    fn on_spin(&mut self, _attempt: u32) -> SpinAction {
+        core::hint::spin_loop();
+        SpinAction::Retry
+    }
+}
+
+/// Retries forever, but waits `2^attempt` spin loop iterations before each retry, capped at
+/// `2^max_exponent`.
+///
+/// Backing off keeps a waiting core from hammering the SIO while the lock is held for long.
+#[derive(Clone, Copy, Debug)]
+pub struct ExponentialBackoff {
+    /// The exponent of the longest wait, at most 31.
+    pub max_exponent: u32,
+}
+
+impl SpinStrategy for ExponentialBackoff {
+    #[inline]
+    fn on_spin(&mut self, attempt: u32) -> SpinAction {
+        for _ in 0..1u32 << attempt.min(self.max_exponent).min(31) {
+            core::hint::spin_loop();
+        }
+        SpinAction::Retry
+    }
+}
+
+/// Retries immediately, but aborts after the given number of retries.
+#[derive(Clone, Copy, Debug)]
+pub struct BoundedRetries(pub u32);
+
+impl SpinStrategy for BoundedRetries {
+    #[inline]
+    fn on_spin(&mut self, attempt: u32) -> SpinAction {
+        if attempt < self.0 {
+            SpinAction::Retry
+        } else {
+            SpinAction::Abort
+        }
+    }
+}
+
+/// Retries forever, but sleeps with `WFE` (wait for event) before each retry.
+///
+/// **Note:** Releasing a spinlock does not signal an event, so the waiting core only wakes up
+/// on the next interrupt or `SEV` instruction. Only use this if the lock holder executes `SEV`
+/// after releasing the lock, or if interrupts occur frequently enough.
+#[cfg(target_arch = "arm")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Wfe;
+
+#[cfg(target_arch = "arm")]
+impl SpinStrategy for Wfe {
+    #[inline]
+    fn on_spin(&mut self, _attempt: u32) -> SpinAction {
+        // SAFETY: `WFE` only waits for an event, it has no other effects.
+        unsafe { core::arch::asm!("wfe", options(nomem, nostack, preserves_flags)) };
+        SpinAction::Retry
+    }
+}
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock, asking `strategy` what to do after every failed attempt.
+    ///
+    /// Returns `None` if the strategy aborted the acquisition.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::spin::{BoundedRetries, ExponentialBackoff};
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static MUTEX: SpinlockMutex<7, i32> = SpinlockMutex::new(0);
+    ///
+    /// if let Some(mut guard) = MUTEX.lock_with_strategy(&mut BoundedRetries(100)) {
+    ///     *guard += 1;
+    /// }
+    ///
+    /// *MUTEX.lock_with_strategy(&mut ExponentialBackoff { max_exponent: 6 }).unwrap() += 1;
+    /// ```
+    #[inline]
+    #[track_caller]
+    pub fn lock_with_strategy<S: SpinStrategy + ?Sized>(&self, strategy: &mut S) -> Option<SpinlockMutexGuard<N, T>> {
+        let mut attempt = 0;
+        loop {
+            if let Some(guard) = self.try_lock() {
+                Self::record_retries(attempt);
+                break Some(guard);
+            }
+            if strategy.on_spin(attempt) == SpinAction::Abort {
+                break None;
+            }
+            attempt = attempt.saturating_add(1);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+/// An event reported to the hook installed with [`set_trace_hook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LockEvent {
+    /// The spinlock was acquired.
+    Acquired,
+    /// The spinlock is about to be released.
+    Released,
+    /// An attempt to claim the spinlock failed because it is held elsewhere.
+    ///
+    /// [`SpinlockMutex::lock`](crate::SpinlockMutex::lock) reports this once before it starts
+    /// spinning, while every failed [`try_lock`](crate::SpinlockMutex::try_lock) reports it again.
+    Contended,
+}
+
+/// The installed hook as a `fn(usize, LockEvent)`, or null.
+static HOOK: AtomicPtr<()> = AtomicPtr::new(core::ptr::null_mut());
+
+/// Installs `hook` to be called on lock events of every [`SpinlockMutex`](crate::SpinlockMutex),
+/// replacing any previously installed hook.
+///
+/// The hook receives the number of the spinlock and the [`LockEvent`]. It is called on the
+/// locking core, with [`Acquired`](LockEvent::Acquired) and [`Released`](LockEvent::Released)
+/// called while the spinlock is held. It thus has to be short, and it must neither lock a
+/// `SpinlockMutex` nor use anything else built on hardware spinlocks (e.g. `critical-section`
+/// on the rp2040), as that may deadlock or recurse. Writing into a buffer owned by the
+/// current core is a good fit.
+///
+/// ```no_run
+/// use rp_spinlockmutex::{set_trace_hook, LockEvent};
+///
+/// fn trace(lock: usize, event: LockEvent) {
+///     // push (lock, event) into a per-core ring buffer
+/// }
+///
+/// set_trace_hook(trace);
+/// ```
+#[inline]
+pub fn set_trace_hook(hook: fn(usize, LockEvent)) {
+    HOOK.store(hook as *mut (), Ordering::Relaxed);
+}
+
+/// Removes the hook installed with [`set_trace_hook`].
+#[inline]
+pub fn clear_trace_hook() {
+    HOOK.store(core::ptr::null_mut(), Ordering::Relaxed);
+}
+
+#[inline]
+pub(crate) fn emit(lock: usize, event: LockEvent) {
+    #[cfg(all(feature = "test-trace", not(target_arch = "arm")))]
+    crate::event_log::record(lock, event);
+    let hook = HOOK.load(Ordering::Relaxed);
+    if !hook.is_null() {
+        // SAFETY: Non-null values are only ever stored by `set_trace_hook`, from a `fn(usize, LockEvent)`.
+        let hook: fn(usize, LockEvent) = unsafe { core::mem::transmute(hook) };
+        hook(lock, event);
+    }
+}
@@ -0,0 +1,59 @@
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::SpinlockMutex;
+
+/// A spinlock mutex that has not been initialized yet and therefore cannot be locked.
+///
+/// This encodes an init-before-use invariant in the type system: the only way to get a lockable
+/// [`SpinlockMutex`] is to hand the initial data to [`init`](Self::init). For the simple case,
+/// [`SpinlockMutex::new`] remains the way to go.
+///
+/// # Example
+///
+/// ```no_run
+/// use rp_spinlockmutex::{SpinlockMutex, UninitSpinlockMutex};
+///
+/// struct Config {
+///     baudrate: u32,
+/// }
+///
+/// let uninit: UninitSpinlockMutex<7> = UninitSpinlockMutex::new();
+/// // uninit.lock(); // ❌ does not compile ❌
+///
+/// let mutex: SpinlockMutex<7, Config> = uninit.init(Config { baudrate: 115_200 });
+/// assert_eq!(mutex.lock().baudrate, 115_200);
+/// ```
+#[must_use = "an uninitialized mutex is useless until it is initialized"]
+pub struct UninitSpinlockMutex<const N: usize>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    _private: (),
+}
+
+impl<const N: usize> UninitSpinlockMutex<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Creates a new uninitialized hardware based spinlock mutex.
+    #[inline]
+    pub const fn new() -> Self {
+        Self { _private: () }
+    }
+
+    /// Initializes the mutex with `data`, making it available for locking.
+    #[inline]
+    pub const fn init<T>(self, data: T) -> SpinlockMutex<N, T> {
+        SpinlockMutex::new(data)
+    }
+}
+
+impl<const N: usize> Default for UninitSpinlockMutex<N>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
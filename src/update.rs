@@ -0,0 +1,71 @@
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::SpinlockMutex;
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Returns a builder collecting updates of the protected value, which are all applied in a
+    /// single critical section on [`commit`](UpdateBuilder::commit).
+    ///
+    /// Writing several updates as `*mutex.lock() = ...` claims and releases the spinlock once per
+    /// statement, so the other core may observe the value half-updated. With the builder, the
+    /// lock is only claimed once when committing, and no lock is held while building. The
+    /// updates are applied in the order they were added.
+    ///
+    /// ```no_run
+    /// use rp_spinlockmutex::SpinlockMutex;
+    ///
+    /// struct Position {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    ///
+    /// static POSITION: SpinlockMutex<7, Position> = SpinlockMutex::new(Position { x: 0, y: 0 });
+    ///
+    /// POSITION.update_builder().then(|p| p.x = 1).then(|p| p.y = 2).commit();
+    /// ```
+    #[inline]
+    pub fn update_builder(&self) -> UpdateBuilder<'_, N, T, fn(&mut T)> {
+        UpdateBuilder {
+            mutex: self,
+            update: |_| {},
+        }
+    }
+}
+
+/// A builder obtained from [`SpinlockMutex::update_builder`], collecting updates of the
+/// protected value until they are applied by [`commit`](Self::commit).
+#[must_use = "the updates are only applied on `commit`"]
+pub struct UpdateBuilder<'a, const N: usize, T: ?Sized, F>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    mutex: &'a SpinlockMutex<N, T>,
+    update: F,
+}
+
+impl<'a, const N: usize, T: ?Sized, F: FnOnce(&mut T)> UpdateBuilder<'a, N, T, F>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Adds `update` to be applied after all previously added updates.
+    #[inline]
+    pub fn then(self, update: impl FnOnce(&mut T)) -> UpdateBuilder<'a, N, T, impl FnOnce(&mut T)> {
+        let previous = self.update;
+        UpdateBuilder {
+            mutex: self.mutex,
+            update: move |data: &mut T| {
+                previous(data);
+                update(data);
+            },
+        }
+    }
+
+    /// Acquires the mutex lock once and applies all updates while holding it.
+    #[inline]
+    pub fn commit(self) {
+        (self.update)(&mut self.mutex.lock());
+    }
+}
@@ -0,0 +1,64 @@
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// Names hardware spinlock `N` in a [`ValidLock`] bound.
+///
+/// This is the hal's `Spinlock<N>`, re-exported so that code generic over the spinlock number
+/// does not need to depend on `rp2040_hal` for its bounds.
+pub type LockNumber<const N: usize> = Spinlock<N>;
+
+/// Implemented by the [`LockNumber`] of each of the 32 hardware spinlocks.
+///
+/// Wrappers generic over the spinlock number bound it with `where LockNumber<N>: ValidLock`.
+/// That bound implies the hal's `Spinlock<N>: SpinlockValid` required by [`SpinlockMutex`],
+/// so the wrapper can use all of its methods.
+///
+/// The trait is sealed and cannot be implemented outside of this crate.
+///
+/// ```no_run
+/// use rp_spinlockmutex::{LockNumber, SpinlockMutex, ValidLock};
+///
+/// pub struct Counter<const N: usize>
+/// where
+///     LockNumber<N>: ValidLock,
+/// {
+///     count: SpinlockMutex<N, u32>,
+/// }
+///
+/// impl<const N: usize> Counter<N>
+/// where
+///     LockNumber<N>: ValidLock,
+/// {
+///     pub const fn new() -> Self {
+///         Self { count: SpinlockMutex::new(0) }
+///     }
+///
+///     pub fn increment(&self) -> u32 {
+///         let mut count = self.count.lock();
+///         *count += 1;
+///         *count
+///     }
+/// }
+///
+/// static COUNTER: Counter<7> = Counter::new();
+/// COUNTER.increment();
+/// ```
+///
+/// The rp2040 has no spinlock 32:
+///
+/// ```compile_fail
+/// use rp_spinlockmutex::{LockNumber, ValidLock};
+///
+/// fn assert_valid<const N: usize>() where LockNumber<N>: ValidLock {}
+///
+/// assert_valid::<32>(); // ❌ not a valid lock ❌
+/// ```
+///
+/// [`SpinlockMutex`]: crate::SpinlockMutex
+pub trait ValidLock: SpinlockValid + private::Sealed {}
+
+impl<const N: usize> private::Sealed for Spinlock<N> where Spinlock<N>: SpinlockValid {}
+impl<const N: usize> ValidLock for Spinlock<N> where Spinlock<N>: SpinlockValid {}
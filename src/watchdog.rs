@@ -0,0 +1,99 @@
+use core::ops::{Deref, DerefMut};
+
+use embedded_hal::watchdog::Watchdog;
+use rp2040_hal::sio::{Spinlock, SpinlockValid};
+
+use crate::{SpinlockMutex, SpinlockMutexGuard};
+
+impl<const N: usize, T: ?Sized> SpinlockMutex<N, T>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Acquires the mutex lock and returns a guard that keeps the watchdog `wd` fed.
+    ///
+    /// The watchdog is fed once the lock is acquired, and can be fed again at any point of the
+    /// critical section with [`WatchdogFeedingGuard::feed`], e.g. between the pages of a long
+    /// flash write. It is fed a last time when the guard is dropped.
+    ///
+    /// The watchdog is *not* fed while waiting for the lock. A deadlock during acquisition thus
+    /// still leads to a watchdog reset, so this guard only prevents resets caused by legitimately
+    /// long critical sections, not by hung ones. Only feed the watchdog as often as the work
+    /// inside the critical section makes progress, otherwise a stuck loop is masked as well.
+    ///
+    /// ```no_run
+    /// use rp2040_hal::{pac, Watchdog};
+    /// use rp_spinlockmutex::SpinlockMutex;
+    /// static FLASH: SpinlockMutex<7, [[u8; 256]; 4]> = SpinlockMutex::new([[0; 256]; 4]);
+    ///
+    /// let mut pac = pac::Peripherals::take().unwrap();
+    /// let mut watchdog = Watchdog::new(pac.WATCHDOG);
+    ///
+    /// let mut guard = FLASH.lock_watchdog_feeding(&mut watchdog);
+    /// for page in 0..4 {
+    ///     guard[page] = [0xFF; 256]; // write_page(page, &guard[page]);
+    ///     guard.feed();
+    /// }
+    /// ```
+    #[inline]
+    pub fn lock_watchdog_feeding<'a, W: Watchdog>(&'a self, wd: &'a mut W) -> WatchdogFeedingGuard<'a, N, T, W> {
+        let guard = self.lock();
+        wd.feed();
+        WatchdogFeedingGuard { guard, watchdog: wd }
+    }
+}
+
+/// A guard obtained from [`SpinlockMutex::lock_watchdog_feeding`].
+///
+/// It behaves like a [`SpinlockMutexGuard`] and additionally holds the watchdog,
+/// which is fed once more right before the spinlock is released on drop.
+#[must_use = "if unused the SpinlockMutex will immediately unlock"]
+pub struct WatchdogFeedingGuard<'a, const N: usize, T: ?Sized, W: Watchdog>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    guard: SpinlockMutexGuard<N, T>,
+    watchdog: &'a mut W,
+}
+
+impl<const N: usize, T: ?Sized, W: Watchdog> WatchdogFeedingGuard<'_, N, T, W>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    /// Feeds the watchdog.
+    #[inline]
+    pub fn feed(&mut self) {
+        self.watchdog.feed();
+    }
+}
+
+impl<const N: usize, T: ?Sized, W: Watchdog> Drop for WatchdogFeedingGuard<'_, N, T, W>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn drop(&mut self) {
+        self.watchdog.feed();
+    }
+}
+
+impl<const N: usize, T: ?Sized, W: Watchdog> Deref for WatchdogFeedingGuard<'_, N, T, W>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<const N: usize, T: ?Sized, W: Watchdog> DerefMut for WatchdogFeedingGuard<'_, N, T, W>
+where
+    Spinlock<N>: SpinlockValid,
+{
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
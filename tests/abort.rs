@@ -0,0 +1,35 @@
+//! Host tests of `panic_release_all`. It releases the locks of every `AbortSafeGuard` of the
+//! current core, and all host threads count as core 0, so this binary has a single test.
+
+use rp_spinlockmutex::{panic_release_all, ContendedBy, SpinlockMutex};
+
+#[test]
+fn forgotten_guards_are_released() {
+    let config: SpinlockMutex<0, i32> = SpinlockMutex::new(0);
+    let state: SpinlockMutex<1, i32> = SpinlockMutex::new(0);
+    let untracked: SpinlockMutex<2, i32> = SpinlockMutex::new(0);
+
+    // What a panic with `panic = "abort"` does to the guards.
+    std::mem::forget(config.lock_abort_safe());
+    *state.lock_abort_safe() += 1;
+    std::mem::forget(state.lock_abort_safe());
+    let untracked_guard = untracked.lock();
+    assert!(config.try_lock().is_none() && state.try_lock().is_none());
+
+    // SAFETY: The forgotten guards are never used again.
+    unsafe { panic_release_all() };
+
+    assert_eq!(config.try_lock().as_deref(), Some(&0));
+    assert_eq!(state.try_lock().as_deref(), Some(&1));
+    assert!(untracked.try_lock().is_none());
+    drop(untracked_guard);
+
+    // The owners were cleared, so locks held without an `AbortSafeGuard` afterwards are
+    // neither reported as owned nor released by another call.
+    let guard = state.lock();
+    assert_eq!(state.try_lock_diag().err(), Some(ContendedBy { core: None }));
+    // SAFETY: No guard of `lock_abort_safe` is live.
+    unsafe { panic_release_all() };
+    assert!(state.try_lock().is_none());
+    drop(guard);
+}
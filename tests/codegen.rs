@@ -0,0 +1,126 @@
+//! Builds the wrappers in `tests/codegen/wrappers.rs` for the rp2040, which covers the parts of
+//! the API that only exist there, such as the conversions between guards and the HAL's
+//! spinlocks. This needs the `thumbv6m-none-eabi` target, like any build of the crate.
+//!
+//! The ignored test also checks that the acquire paths compile down to a handful of
+//! instructions, close to a hand-written spinlock sequence: a load of the `SPINLOCK` register,
+//! a `dmb` on each side of the data access and a store to release it. It disassembles the
+//! wrappers with `llvm-objdump` (or `rust-objdump` of `cargo-binutils`, or the one named by
+//! `OBJDUMP`):
+//!
+//! ```sh
+//! cargo test --target x86_64-unknown-linux-gnu --test codegen -- --ignored
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The wrappers and the most instructions each may compile to.
+const THRESHOLDS: [(&str, usize); 3] = [
+    ("codegen_lock", 32),
+    ("codegen_try_lock", 24),
+    // Two acquisitions, one released through the HAL and one through a rebuilt guard.
+    ("codegen_hal_round_trip", 64),
+];
+
+#[test]
+fn wrappers_build_for_the_rp2040() {
+    build_wrappers("build");
+}
+
+#[test]
+#[ignore = "needs llvm-objdump"]
+fn acquire_paths_stay_small() {
+    let object = build_wrappers("count");
+
+    for (symbol, max) in THRESHOLDS {
+        let disassembly = run(Command::new(objdump())
+            .args(["-d", "--no-show-raw-insn"])
+            .arg(format!("--disassemble-symbols={}", symbol))
+            .arg(&object));
+        let count = instructions(&disassembly);
+        assert!(count > 0, "`{}` not found in the disassembly:\n{}", symbol, disassembly);
+        assert!(
+            count <= max,
+            "`{}` compiles to {} instructions, more than {}:\n{}",
+            symbol,
+            count,
+            max,
+            disassembly
+        );
+    }
+}
+
+/// Builds the crate and the wrappers for the rp2040, returning the path of the object file of
+/// the wrappers. Each test passes its own `name` for the object file, as they run in parallel.
+fn build_wrappers(name: &str) -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // A separate target directory, so that the build does not wait for the one running the tests.
+    let target_dir = manifest_dir.join("target").join("codegen");
+    let release = target_dir.join("thumbv6m-none-eabi").join("release");
+    let object = target_dir.join(format!("wrappers-{}.o", name));
+
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    run(Command::new(cargo)
+        .current_dir(manifest_dir)
+        .args("build --lib --release --target thumbv6m-none-eabi --target-dir".split(' '))
+        .arg(&target_dir));
+    run(Command::new("rustc")
+        .args("--edition 2021 --crate-type lib --crate-name codegen_wrappers".split(' '))
+        .args("--target thumbv6m-none-eabi -C opt-level=3 -C codegen-units=1".split(' '))
+        .arg("-L")
+        .arg(format!("dependency={}", release.join("deps").display()))
+        // The proc macros of the dependencies are built for the host.
+        .arg("-L")
+        .arg(format!(
+            "dependency={}",
+            target_dir.join("release").join("deps").display()
+        ))
+        .arg("--extern")
+        .arg(format!(
+            "rp_spinlockmutex={}",
+            release.join("librp_spinlockmutex.rlib").display()
+        ))
+        .arg("--emit")
+        .arg(format!("obj={}", object.display()))
+        .arg(manifest_dir.join("tests").join("codegen").join("wrappers.rs")));
+    object
+}
+
+/// Returns the first disassembler found on the `PATH`.
+fn objdump() -> String {
+    if let Ok(objdump) = std::env::var("OBJDUMP") {
+        return objdump;
+    }
+    ["llvm-objdump", "rust-objdump"]
+        .into_iter()
+        .find(|tool| Command::new(tool).arg("--version").output().is_ok())
+        .expect("neither llvm-objdump nor rust-objdump is installed")
+        .into()
+}
+
+/// Runs `command`, panicking if it fails, and returns its standard output.
+fn run(command: &mut Command) -> String {
+    let output = command
+        .output()
+        .unwrap_or_else(|error| panic!("failed to run {:?}: {}", command, error));
+    assert!(
+        output.status.success(),
+        "{:?} failed:\n{}",
+        command,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Counts the instructions of a disassembly, skipping literal pools such as `.word 0xd000011c`.
+fn instructions(disassembly: &str) -> usize {
+    disassembly
+        .lines()
+        .filter_map(|line| line.trim_start().split_once(':'))
+        .filter(|(address, instruction)| {
+            address.chars().all(|c| c.is_ascii_hexdigit())
+                && !instruction.split('\t').any(|field| field.starts_with('.'))
+        })
+        .count()
+}
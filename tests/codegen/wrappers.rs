@@ -0,0 +1,39 @@
+#![no_std]
+
+use rp_spinlockmutex::SpinlockMutex;
+
+static COUNTER: SpinlockMutex<7, u32> = SpinlockMutex::new(0);
+
+#[no_mangle]
+pub fn codegen_lock() {
+    *COUNTER.lock() += 1;
+}
+
+#[no_mangle]
+pub fn codegen_try_lock() -> bool {
+    match COUNTER.try_lock() {
+        Some(mut counter) => {
+            *counter += 1;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Hands the claimed spinlock to code using the HAL directly, and builds a guard from it.
+#[no_mangle]
+pub fn codegen_hal_round_trip() {
+    use core::sync::atomic::{fence, Ordering};
+    use rp_spinlockmutex::SpinlockMutexGuard;
+
+    let (spinlock, data) = SpinlockMutexGuard::into_hal(COUNTER.lock());
+    // SAFETY: `spinlock` is still claimed.
+    unsafe { *data += 1 };
+    fence(Ordering::Release);
+    drop(spinlock);
+
+    let (spinlock, data) = SpinlockMutexGuard::into_hal(COUNTER.lock());
+    // SAFETY: `data` points into `COUNTER`, which is protected by `spinlock`.
+    let mut counter = unsafe { SpinlockMutexGuard::from_hal(spinlock, data) };
+    *counter += 1;
+}
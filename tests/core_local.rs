@@ -0,0 +1,71 @@
+//! Host tests of `CoreLocal`. The tests with both cores need the `std-testing` feature, whose
+//! threads can emulate core 1.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use rp_spinlockmutex::diag::locked_mask;
+use rp_spinlockmutex::CoreLocal;
+
+#[test]
+fn local_and_other_access_their_own_copies() {
+    let counters: CoreLocal<0, u32> = CoreLocal::new(1, 10);
+
+    counters.with_local(|local| *local += 1);
+    counters.with_other(|other| *other += 1);
+
+    assert_eq!(counters.with_local(|local| *local), 2);
+    assert_eq!(counters.with_other(|other| *other), 11);
+}
+
+#[test]
+fn with_other_claims_the_spinlock() {
+    let counters: CoreLocal<1, u32> = CoreLocal::new(0, 0);
+
+    counters.with_local(|_| assert_eq!(locked_mask() & 1 << 1, 0));
+    counters.with_other(|_| assert_ne!(locked_mask() & 1 << 1, 0));
+}
+
+#[test]
+fn reentrant_local_access_panics_without_leaking_the_flag() {
+    let counters: CoreLocal<2, u32> = CoreLocal::new(0, 0);
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        counters.with_local(|_| counters.with_local(|_| ()));
+    }));
+    let message = *result.unwrap_err().downcast::<String>().unwrap();
+    assert_eq!(message, "CoreLocal<2, _> accessed reentrantly on core 0");
+
+    assert_eq!(counters.with_local(|local| *local), 0);
+}
+
+#[cfg(feature = "std-testing")]
+#[test]
+fn local_accesses_and_visits_of_both_cores_are_exclusive() {
+    use rp_spinlockmutex::CoreId;
+
+    static COUNTERS: CoreLocal<3, u64> = CoreLocal::new(0, 0);
+
+    // Each increment yields between reading and writing the value, so overlapping accesses
+    // lose updates.
+    fn increment(value: &mut u64) {
+        let previous = *value;
+        std::thread::yield_now();
+        *value = previous + 1;
+    }
+
+    let core1 = std::thread::spawn(|| {
+        CoreId::Core1.emulate();
+        for _ in 0..2_000 {
+            COUNTERS.with_local(increment);
+            COUNTERS.with_other(increment);
+        }
+    });
+    for _ in 0..2_000 {
+        COUNTERS.with_local(increment);
+        COUNTERS.with_other(increment);
+    }
+    core1.join().unwrap();
+
+    assert_eq!(COUNTERS.with_local(|local| *local), 4_000);
+    assert_eq!(COUNTERS.with_other(|other| *other), 4_000);
+}
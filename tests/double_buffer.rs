@@ -0,0 +1,59 @@
+//! Host tests of `DoubleBuffer`.
+
+use rp_spinlockmutex::{DoubleBuffer, DoubleBufferGuard};
+
+#[test]
+fn swap_exchanges_the_roles_of_the_buffers() {
+    let buffers: DoubleBuffer<0, 1, [u8; 4]> = DoubleBuffer::new([0; 4], [0; 4]);
+
+    *buffers.fill_buffer() = [1; 4];
+    assert!(matches!(buffers.fill_buffer(), DoubleBufferGuard::A(_)));
+    assert_eq!(*buffers.drain_buffer(), [0; 4]);
+
+    buffers.swap();
+    assert!(matches!(buffers.fill_buffer(), DoubleBufferGuard::B(_)));
+    assert_eq!(*buffers.drain_buffer(), [1; 4]);
+
+    buffers.swap();
+    assert_eq!(*buffers.fill_buffer(), [1; 4]);
+}
+
+#[test]
+fn filling_and_draining_use_different_spinlocks() {
+    let buffers: DoubleBuffer<2, 3, [u8; 4]> = DoubleBuffer::new([0; 4], [0; 4]);
+
+    let mut fill = buffers.fill_buffer();
+    fill[0] = 1;
+    // Would deadlock if both guards used the same spinlock.
+    let drain = buffers.drain_buffer();
+    assert_eq!(*drain, [0; 4]);
+
+    // Guards obtained before the swap keep referring to their buffer.
+    buffers.swap();
+    fill[1] = 2;
+    drop((fill, drain));
+    assert_eq!(*buffers.drain_buffer(), [1, 2, 0, 0]);
+}
+
+#[test]
+fn buffers_drained_while_filling_are_never_torn() {
+    static SAMPLES: DoubleBuffer<4, 5, [u32; 64]> = DoubleBuffer::new([0; 64], [0; 64]);
+
+    let filler = std::thread::spawn(|| {
+        for block in 1..=1000 {
+            SAMPLES.fill_buffer().fill(block);
+            SAMPLES.swap();
+        }
+    });
+    let mut last = 0;
+    while !filler.is_finished() {
+        let buffer = SAMPLES.drain_buffer();
+        assert!(buffer.iter().all(|&sample| sample == buffer[0]));
+        // The other buffer is at most one block behind.
+        assert!(buffer[0] + 1 >= last);
+        last = buffer[0];
+    }
+    filler.join().unwrap();
+
+    assert_eq!(*SAMPLES.drain_buffer(), [1000; 64]);
+}
@@ -0,0 +1,184 @@
+//! Host tests of the order in which guards release their spinlock and undo their other state,
+//! recorded with the `test-trace` event log.
+#![cfg(feature = "test-trace")]
+
+use rp2040_hal::pac::Interrupt;
+use rp_spinlockmutex::diag::{drain_events, TraceKind};
+use rp_spinlockmutex::{HostNvic, SpinlockMutex};
+
+/// The log is global, so the tests take turns and drain it before they start. The events of
+/// this lock are filtered out.
+static SERIAL: SpinlockMutex<31, ()> = SpinlockMutex::new(());
+
+/// Resets the NVIC emulation now and when the returned value is dropped, also when a test fails,
+/// so that no test sees the interrupts set up by another.
+fn fresh_nvic() -> impl Drop {
+    struct Reset;
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            HostNvic::reset();
+        }
+    }
+    HostNvic::reset();
+    Reset
+}
+
+fn recorded() -> Vec<TraceKind> {
+    drain_events()
+        .map(|event| event.kind)
+        .filter(|kind| *kind != TraceKind::Acquired(31) && *kind != TraceKind::Released(31))
+        .collect()
+}
+
+#[test]
+fn masking_guard_releases_before_unmasking() {
+    let _serial = SERIAL.lock();
+    let _nvic = fresh_nvic();
+    let mutex: SpinlockMutex<0, u32> = SpinlockMutex::new(0);
+    HostNvic::enable(Interrupt::ADC_IRQ_FIFO);
+    drain_events().for_each(drop);
+
+    let mut guard = mutex.lock_masking(Interrupt::ADC_IRQ_FIFO);
+    *guard += 1;
+    assert!(!HostNvic::is_enabled(Interrupt::ADC_IRQ_FIFO));
+    drop(guard);
+
+    assert!(HostNvic::is_enabled(Interrupt::ADC_IRQ_FIFO));
+    let irq = Interrupt::ADC_IRQ_FIFO as u16;
+    assert_eq!(
+        recorded(),
+        [
+            TraceKind::Masked(irq),
+            TraceKind::Acquired(0),
+            TraceKind::Released(0),
+            TraceKind::Unmasked(irq),
+        ]
+    );
+}
+
+#[test]
+fn masking_guard_leaves_a_disabled_interrupt_disabled() {
+    let _serial = SERIAL.lock();
+    let _nvic = fresh_nvic();
+    let mutex: SpinlockMutex<1, u32> = SpinlockMutex::new(0);
+    HostNvic::disable(Interrupt::I2C0_IRQ);
+    drain_events().for_each(drop);
+
+    drop(mutex.lock_masking(Interrupt::I2C0_IRQ));
+
+    assert!(!HostNvic::is_enabled(Interrupt::I2C0_IRQ));
+    let irq = Interrupt::I2C0_IRQ as u16;
+    assert_eq!(
+        recorded(),
+        [TraceKind::Masked(irq), TraceKind::Acquired(1), TraceKind::Released(1)]
+    );
+}
+
+#[test]
+fn ceiling_guard_releases_before_unmasking() {
+    let _serial = SERIAL.lock();
+    let _nvic = fresh_nvic();
+    let mutex: SpinlockMutex<2, u32> = SpinlockMutex::new(0);
+    let (low, medium, high) = (Interrupt::UART0_IRQ, Interrupt::UART1_IRQ, Interrupt::SPI0_IRQ);
+    for (irq, priority) in [(low, 0xc0), (medium, 0x40), (high, 0x00)] {
+        HostNvic::set_priority(irq, priority);
+        HostNvic::enable(irq);
+    }
+    drain_events().for_each(drop);
+
+    let guard = mutex.lock_ceiling(0x40);
+    assert!(!HostNvic::is_enabled(low) && !HostNvic::is_enabled(medium));
+    assert!(HostNvic::is_enabled(high));
+    drop(guard);
+
+    assert!(HostNvic::is_enabled(low) && HostNvic::is_enabled(medium));
+    let (low, medium) = (low as u16, medium as u16);
+    let (first, second) = (low.min(medium), low.max(medium));
+    assert_eq!(
+        recorded(),
+        [
+            TraceKind::Masked(first),
+            TraceKind::Masked(second),
+            TraceKind::Acquired(2),
+            TraceKind::Released(2),
+            TraceKind::Unmasked(first),
+            TraceKind::Unmasked(second),
+        ]
+    );
+}
+
+/// Whether spinlock 4 could be locked while the guard of spinlock 3 was being released.
+#[cfg(debug_assertions)]
+static LOWER_LOCKED_ON_RELEASE: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+#[cfg(debug_assertions)]
+static LOWER: rp_spinlockmutex::OrderedSpinlockMutex<4, 0, u32> = rp_spinlockmutex::OrderedSpinlockMutex::new(0);
+
+#[cfg(debug_assertions)]
+#[test]
+fn ordered_guard_clears_its_order_before_releasing() {
+    use std::sync::atomic::Ordering;
+
+    use rp_spinlockmutex::{set_trace_hook, LockEvent, OrderedSpinlockMutex};
+
+    // The hook runs while spinlock 3 is still claimed. Locking a lower order only succeeds
+    // if the guard no longer counts as held then.
+    fn on_release(lock: usize, event: LockEvent) {
+        if lock == 3 && event == LockEvent::Released {
+            let locked = std::panic::catch_unwind(|| LOWER.try_lock().is_some()).unwrap_or(false);
+            LOWER_LOCKED_ON_RELEASE.store(locked, Ordering::Relaxed);
+        }
+    }
+
+    let _serial = SERIAL.lock();
+    let higher: OrderedSpinlockMutex<3, 5, u32> = OrderedSpinlockMutex::new(0);
+    set_trace_hook(on_release);
+
+    drop(higher.lock());
+
+    assert!(LOWER_LOCKED_ON_RELEASE.load(Ordering::Relaxed));
+    rp_spinlockmutex::clear_trace_hook();
+}
+
+#[cfg(all(feature = "core-pinning", debug_assertions))]
+#[test]
+fn masking_guard_restores_the_interrupt_if_locking_panics() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use rp_spinlockmutex::CoreId;
+
+    let _serial = SERIAL.lock();
+    let _nvic = fresh_nvic();
+    let mutex: SpinlockMutex<5, u32> = SpinlockMutex::new_pinned(CoreId::Core1, 0);
+    HostNvic::enable(Interrupt::I2C1_IRQ);
+    drain_events().for_each(drop);
+
+    let locking = catch_unwind(AssertUnwindSafe(|| drop(mutex.lock_masking(Interrupt::I2C1_IRQ))));
+
+    assert!(locking.is_err());
+    assert!(HostNvic::is_enabled(Interrupt::I2C1_IRQ));
+    let irq = Interrupt::I2C1_IRQ as u16;
+    assert_eq!(recorded(), [TraceKind::Masked(irq), TraceKind::Unmasked(irq)]);
+}
+
+#[cfg(all(feature = "core-pinning", debug_assertions))]
+#[test]
+fn ceiling_guard_restores_the_interrupts_if_locking_panics() {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use rp_spinlockmutex::CoreId;
+
+    let _serial = SERIAL.lock();
+    let _nvic = fresh_nvic();
+    let mutex: SpinlockMutex<6, u32> = SpinlockMutex::new_pinned(CoreId::Core1, 0);
+    HostNvic::set_priority(Interrupt::RTC_IRQ, 0x80);
+    HostNvic::enable(Interrupt::RTC_IRQ);
+    drain_events().for_each(drop);
+
+    let locking = catch_unwind(AssertUnwindSafe(|| drop(mutex.lock_ceiling(0x80))));
+
+    assert!(locking.is_err());
+    assert!(HostNvic::is_enabled(Interrupt::RTC_IRQ));
+    let irq = Interrupt::RTC_IRQ as u16;
+    assert!(recorded().ends_with(&[TraceKind::Unmasked(irq)]));
+}
@@ -0,0 +1,97 @@
+//! Host tests of the `test-trace` event log.
+#![cfg(feature = "test-trace")]
+
+use rp_spinlockmutex::diag::{drain_events, TraceEvent, TraceKind};
+use rp_spinlockmutex::SpinlockMutex;
+
+/// The log is global, so the tests take turns and drain it before they start. The events of
+/// this lock are filtered out.
+static SERIAL: SpinlockMutex<31, ()> = SpinlockMutex::new(());
+
+fn lock_of(event: &TraceEvent) -> Option<usize> {
+    match event.kind {
+        TraceKind::Acquired(lock) | TraceKind::Released(lock) => Some(lock),
+        TraceKind::Masked(_) | TraceKind::Unmasked(_) => None,
+    }
+}
+
+fn events_of(lock: usize) -> Vec<TraceEvent> {
+    drain_events().filter(|event| lock_of(event) == Some(lock)).collect()
+}
+
+#[test]
+fn acquisitions_and_releases_are_recorded_in_order() {
+    let _serial = SERIAL.lock();
+    let outer: SpinlockMutex<0, u32> = SpinlockMutex::new(0);
+    let inner: SpinlockMutex<1, u32> = SpinlockMutex::new(0);
+    drain_events().for_each(drop);
+
+    {
+        let _outer = outer.lock();
+        *inner.lock() += 1;
+    }
+    assert!(outer.try_lock().is_some());
+
+    let events: Vec<_> = drain_events()
+        .filter(|event| lock_of(event) != Some(31))
+        .map(|event| (event.core, event.kind))
+        .collect();
+    assert_eq!(
+        events,
+        [
+            (0, TraceKind::Acquired(0)),
+            (0, TraceKind::Acquired(1)),
+            (0, TraceKind::Released(1)),
+            (0, TraceKind::Released(0)),
+            (0, TraceKind::Acquired(0)),
+            (0, TraceKind::Released(0)),
+        ]
+    );
+    assert!(drain_events().all(|event| lock_of(&event) == Some(31)));
+}
+
+#[test]
+fn only_the_latest_events_are_kept() {
+    let _serial = SERIAL.lock();
+    let mutex: SpinlockMutex<2, u32> = SpinlockMutex::new(0);
+    drain_events().for_each(drop);
+
+    for _ in 0..200 {
+        *mutex.lock() += 1;
+    }
+
+    let events = events_of(2);
+    assert_eq!(events.len(), 256);
+    assert!(events.windows(2).all(|pair| pair[0].timestamp < pair[1].timestamp));
+    assert_eq!(events.last().map(|event| event.kind), Some(TraceKind::Released(2)));
+}
+
+#[cfg(feature = "std-testing")]
+#[test]
+fn contending_cores_never_interleave_acquisitions_and_releases() {
+    use rp_spinlockmutex::CoreId;
+
+    static MUTEX: SpinlockMutex<3, u32> = SpinlockMutex::new(0);
+    let _serial = SERIAL.lock();
+    drain_events().for_each(drop);
+
+    let core1 = std::thread::spawn(|| {
+        CoreId::Core1.emulate();
+        for _ in 0..50 {
+            *MUTEX.lock() += 1;
+        }
+    });
+    for _ in 0..50 {
+        *MUTEX.lock() += 1;
+    }
+    core1.join().unwrap();
+
+    let events = events_of(3);
+    assert_eq!(events.len(), 200);
+    for pair in events.chunks(2) {
+        assert_eq!(pair[0].kind, TraceKind::Acquired(3));
+        assert_eq!(pair[1].kind, TraceKind::Released(3));
+        assert_eq!(pair[0].core, pair[1].core);
+    }
+    assert_eq!(events.iter().filter(|event| event.core == 1).count(), 100);
+}
@@ -0,0 +1,87 @@
+//! Host tests of `FairSpinlockMutex`. Threads count as core 0 unless they emulate core 1 with
+//! the `std-testing` feature, so only those tests cover the turns.
+
+use rp_spinlockmutex::FairSpinlockMutex;
+
+#[test]
+fn try_lock_fails_while_locked() {
+    let mutex: FairSpinlockMutex<0, i32> = FairSpinlockMutex::new(42);
+
+    let guard = mutex.lock();
+    assert!(mutex.try_lock().is_none());
+    drop(guard);
+
+    assert_eq!(mutex.try_lock().as_deref(), Some(&42));
+}
+
+#[test]
+fn relocking_without_a_waiting_core_succeeds() {
+    static COUNTER: FairSpinlockMutex<1, u32> = FairSpinlockMutex::new(0);
+
+    let threads: Vec<_> = (0..2)
+        .map(|_| {
+            std::thread::spawn(|| {
+                for _ in 0..1000 {
+                    *COUNTER.lock() += 1;
+                }
+            })
+        })
+        .collect();
+    threads.into_iter().for_each(|thread| thread.join().unwrap());
+
+    assert_eq!(*COUNTER.lock(), 2000);
+}
+
+/// Set once the thread of `contending_cores_take_turns` emulates core 1.
+#[cfg(feature = "std-testing")]
+static STARTED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// The number of times spinlock 2 was reported acquired to the trace hook.
+#[cfg(all(feature = "std-testing", feature = "trace"))]
+static TRACED: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+#[cfg(feature = "std-testing")]
+#[test]
+fn contending_cores_take_turns() {
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    use rp_spinlockmutex::CoreId;
+
+    static ORDER: FairSpinlockMutex<2, Vec<CoreId>> = FairSpinlockMutex::new(Vec::new());
+
+    #[cfg(feature = "trace")]
+    rp_spinlockmutex::set_trace_hook(|lock, event| {
+        if lock == 2 && event == rp_spinlockmutex::LockEvent::Acquired {
+            TRACED.fetch_add(1, Ordering::Relaxed);
+        }
+    });
+
+    let mut order = ORDER.lock();
+    order.push(CoreId::current());
+    let core1 = std::thread::spawn(|| {
+        CoreId::Core1.emulate();
+        STARTED.store(true, Ordering::Relaxed);
+        ORDER.lock().push(CoreId::Core1);
+    });
+    while !STARTED.load(Ordering::Relaxed) {
+        std::thread::yield_now();
+    }
+    // Gives core 1 time to start waiting in `lock`.
+    std::thread::sleep(Duration::from_millis(10));
+    drop(order);
+
+    // Core 0 held the lock last, so core 1 gets its turn first.
+    ORDER.lock().push(CoreId::Core0);
+    core1.join().unwrap();
+    assert_eq!(*ORDER.lock(), [CoreId::Core0, CoreId::Core1, CoreId::Core0]);
+
+    // The turn given up by core 0 is not an acquisition.
+    #[cfg(feature = "metrics")]
+    assert_eq!(rp_spinlockmutex::diag::lock_stats(2).acquisitions, 4);
+    #[cfg(feature = "trace")]
+    {
+        rp_spinlockmutex::clear_trace_hook();
+        assert_eq!(TRACED.load(Ordering::Relaxed), 4);
+    }
+}
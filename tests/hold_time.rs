@@ -0,0 +1,61 @@
+//! Host tests of `SpinlockMutex::lock_budgeted_with`, using a clock advanced by hand.
+#![cfg(feature = "hold-time")]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+use rp_spinlockmutex::{set_hold_time_hook, MonotonicCycles, SpinlockMutex};
+
+struct TestClock(AtomicU64);
+
+impl TestClock {
+    fn advance(&self, ticks: u64) {
+        self.0.fetch_add(ticks, Ordering::Relaxed);
+    }
+}
+
+impl MonotonicCycles for TestClock {
+    fn now(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+static VIOLATION: (AtomicUsize, AtomicU32, AtomicU32) = (AtomicUsize::new(0), AtomicU32::new(0), AtomicU32::new(0));
+
+fn record_violation(n: usize, held: u32, max: u32) {
+    VIOLATION.0.store(n, Ordering::Relaxed);
+    VIOLATION.1.store(held, Ordering::Relaxed);
+    VIOLATION.2.store(max, Ordering::Relaxed);
+}
+
+// The hook is global, so this is the only test of the binary.
+#[test]
+fn holds_are_checked_against_the_budget_in_ticks_of_the_clock() {
+    let clock = TestClock(AtomicU64::new(1000));
+    let mutex: SpinlockMutex<5, u32> = SpinlockMutex::new(0);
+
+    let mut guard = mutex.lock_budgeted_with(&clock, 10);
+    *guard += 1;
+    clock.advance(10);
+    drop(guard);
+
+    let guard = mutex.lock_budgeted_with(&clock, 10);
+    clock.advance(11);
+    let result = catch_unwind(AssertUnwindSafe(|| drop(guard)));
+    if cfg!(debug_assertions) {
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert_eq!(
+            message,
+            "SpinlockMutex<5, _> was held for 11 ticks, exceeding its budget of 10 ticks"
+        );
+    }
+    assert!(mutex.try_lock().is_some(), "the lock is released despite the panic");
+
+    set_hold_time_hook(record_violation);
+    let guard = mutex.lock_budgeted_with(&clock, 10);
+    clock.advance(u64::from(u32::MAX) + 1);
+    drop(guard);
+    assert_eq!(VIOLATION.0.load(Ordering::Relaxed), 5);
+    assert_eq!(VIOLATION.1.load(Ordering::Relaxed), u32::MAX);
+    assert_eq!(VIOLATION.2.load(Ordering::Relaxed), 10);
+}
@@ -0,0 +1,446 @@
+//! Host tests of the basic locking behavior, using the `HostSpinlock` backend.
+//!
+//! Run with `cargo test --target x86_64-unknown-linux-gnu --tests` (or your host's target
+//! triple). The emulated spinlocks are global like the hardware, so every test uses its own
+//! lock number to be independent of the others running in parallel.
+
+use std::cell::{Cell, RefCell, UnsafeCell};
+
+use rp_spinlockmutex::{ContendedBy, CoreId, IrqShared, SpinlockCell, SpinlockMutex, SpinlockMutexGuard};
+
+#[test]
+fn try_lock_fails_while_locked() {
+    let mutex: SpinlockMutex<0, i32> = SpinlockMutex::new(42);
+
+    let guard = mutex.lock();
+    assert!(mutex.try_lock().is_none());
+    assert_eq!(*guard, 42);
+}
+
+#[test]
+fn try_lock_succeeds_after_drop() {
+    let mutex: SpinlockMutex<1, i32> = SpinlockMutex::new(42);
+
+    let mut guard = mutex.lock();
+    *guard += 1;
+    drop(guard);
+
+    assert_eq!(mutex.try_lock().as_deref(), Some(&43));
+}
+
+#[test]
+fn unlock_releases() {
+    let mutex: SpinlockMutex<2, i32> = SpinlockMutex::new(42);
+
+    let guard = mutex.lock();
+    SpinlockMutex::unlock(guard);
+
+    assert!(mutex.try_lock().is_some());
+}
+
+#[test]
+fn mutexes_with_the_same_number_contend() {
+    let a: SpinlockMutex<3, i32> = SpinlockMutex::new(0);
+    let b: SpinlockMutex<3, u8> = SpinlockMutex::new(0);
+
+    let _guard = a.lock();
+    assert!(b.try_lock().is_none());
+}
+
+#[test]
+fn try_lock_diag_reports_the_recorded_owner() {
+    let mutex: SpinlockMutex<4, i32> = SpinlockMutex::new(0);
+
+    let guard = mutex.lock();
+    assert_eq!(mutex.try_lock_diag().err(), Some(ContendedBy { core: None }));
+    drop(guard);
+
+    let _guard = mutex.lock_abort_safe();
+    assert_eq!(
+        mutex.try_lock_diag().err(),
+        Some(ContendedBy {
+            core: Some(CoreId::Core0)
+        })
+    );
+}
+
+#[test]
+fn take_once_delivers_exactly_once() {
+    static VALUE: SpinlockMutex<5, Option<i32>> = SpinlockMutex::new(None);
+
+    for round in 0..100 {
+        *VALUE.lock() = Some(round);
+
+        let racers: Vec<_> = (0..2).map(|_| std::thread::spawn(|| VALUE.take_once())).collect();
+        let taken: Vec<_> = racers.into_iter().filter_map(|racer| racer.join().unwrap()).collect();
+
+        assert_eq!(taken, [round]);
+        assert_eq!(VALUE.take_once(), None);
+    }
+}
+
+#[test]
+fn replace_cell_swaps_the_storage_under_the_lock() {
+    static FRAME: SpinlockMutex<6, [u8; 4]> = SpinlockMutex::new([0; 4]);
+
+    let holder = FRAME.lock();
+    let swapper = std::thread::spawn(|| FRAME.replace_cell(UnsafeCell::new([1, 2, 3, 4])));
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    assert_eq!(*holder, [0; 4]);
+    drop(holder);
+
+    assert_eq!(swapper.join().unwrap().into_inner(), [0; 4]);
+    assert_eq!(*FRAME.lock(), [1, 2, 3, 4]);
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn guard_formats_into_the_locked_buffer() {
+    use std::fmt::Write;
+
+    let log: SpinlockMutex<7, heapless::String<32>> = SpinlockMutex::new(heapless::String::new());
+
+    write!(log.lock(), "temperature: {}", 21).unwrap();
+    writeln!(log.lock(), ", humidity: {}", 40).unwrap();
+
+    assert_eq!(*log.lock(), "temperature: 21, humidity: 40\n");
+}
+
+#[test]
+fn fetch_add_counts_every_increment_of_both_threads() {
+    static EVENTS: SpinlockMutex<8, u32> = SpinlockMutex::new(0);
+
+    let threads: Vec<_> = (0..2)
+        .map(|_| {
+            std::thread::spawn(|| {
+                for _ in 0..1000 {
+                    EVENTS.increment();
+                }
+            })
+        })
+        .collect();
+    threads.into_iter().for_each(|thread| thread.join().unwrap());
+
+    assert_eq!(EVENTS.fetch_sub(2000), 2000);
+    assert_eq!(EVENTS.fetch_sub(1), 0);
+    assert_eq!(EVENTS.checked_fetch_add(1), None);
+    assert_eq!(EVENTS.checked_fetch_sub(1), Some(u32::MAX));
+}
+
+fn assert_sync<T: Sync>(_: &T) {}
+
+#[test]
+fn send_but_not_sync_payloads_are_shared_between_threads() {
+    static CELL: SpinlockMutex<9, Cell<u32>> = SpinlockMutex::new(Cell::new(0));
+    static LOG: SpinlockMutex<10, RefCell<Vec<u32>>> = SpinlockMutex::new(RefCell::new(Vec::new()));
+    assert_sync(&CELL);
+    assert_sync(&LOG);
+
+    let threads: Vec<_> = (0..2)
+        .map(|_| {
+            std::thread::spawn(|| {
+                for _ in 0..1000 {
+                    let cell = CELL.lock();
+                    cell.set(cell.get() + 1);
+                    LOG.lock().borrow_mut().push(cell.get());
+                }
+            })
+        })
+        .collect();
+    threads.into_iter().for_each(|thread| thread.join().unwrap());
+
+    assert_eq!(CELL.lock().get(), 2000);
+    let log = LOG.lock();
+    assert_eq!(log.borrow().len(), 2000);
+    assert!(log.borrow().windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+/// Polls `future` to completion, assuming it never returns `Pending`.
+fn poll_ready<F: std::future::Future>(future: F) -> F::Output {
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RawWaker::new(std::ptr::null(), &VTABLE), drop, drop, drop);
+    // SAFETY: The vtable functions do nothing, so the null data pointer is never accessed.
+    let waker = unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) };
+    match Box::pin(future).as_mut().poll(&mut Context::from_waker(&waker)) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("the future is not ready"),
+    }
+}
+
+#[test]
+fn with_lock_async_releases_before_completing() {
+    let mutex: SpinlockMutex<11, i32> = SpinlockMutex::new(1);
+
+    let doubled = poll_ready(mutex.with_lock_async(|value| {
+        *value *= 2;
+        *value
+    }));
+
+    assert_eq!(doubled, 2);
+    assert_eq!(mutex.try_lock().as_deref(), Some(&2));
+}
+
+#[test]
+fn lock_range_projects_to_the_window() {
+    let buffer: SpinlockMutex<12, [u8; 8]> = SpinlockMutex::new([0; 8]);
+
+    let mut window = buffer.lock_range(2..5);
+    window.fill(1);
+    assert_eq!(window.len(), 3);
+    assert!(buffer.try_lock_range(0..1).is_none());
+    drop(window);
+
+    assert_eq!(*buffer.lock(), [0, 0, 1, 1, 1, 0, 0, 0]);
+    assert_eq!(buffer.try_lock_range(8..8).as_deref(), Some(&[][..]));
+}
+
+#[test]
+fn lock_range_panics_out_of_bounds_without_locking() {
+    let buffer: SpinlockMutex<13, [u8; 8]> = SpinlockMutex::new([0; 8]);
+
+    assert!(std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| drop(buffer.lock_range(4..9)))).is_err());
+    assert!(buffer.try_lock().is_some());
+}
+
+#[test]
+fn map_keeps_the_lock_until_dropped() {
+    let mutex: SpinlockMutex<14, (i32, i32)> = SpinlockMutex::new((0, 0));
+
+    let mut y = SpinlockMutexGuard::map(mutex.lock(), |(_, y)| y);
+    *y = 2;
+    assert!(mutex.try_lock().is_none());
+    drop(y);
+
+    assert_eq!(*mutex.lock(), (0, 2));
+}
+
+#[test]
+fn raw_held_round_trips_and_releases_on_drop() {
+    let mutex: SpinlockMutex<15, i32> = SpinlockMutex::new(0);
+    let other: SpinlockMutex<15, i32> = SpinlockMutex::new(10);
+
+    let held = SpinlockMutexGuard::into_raw(mutex.lock());
+    assert_eq!(held.spinlock_number(), 15);
+    assert!(mutex.try_lock().is_none());
+
+    let mut guard = SpinlockMutexGuard::from_raw(held, &other);
+    *guard += 1;
+    let held = SpinlockMutexGuard::into_raw(guard);
+    assert!(other.try_lock().is_none());
+
+    // Dropping the token without reconstructing a guard still releases the lock
+    drop(held);
+    assert_eq!(other.try_lock().as_deref(), Some(&11));
+    assert_eq!(mutex.try_lock().as_deref(), Some(&0));
+}
+
+#[test]
+fn lock_all_claims_the_shared_spinlock_once() {
+    let channels: [SpinlockMutex<17, u16>; 3] = [SpinlockMutex::new(1), SpinlockMutex::new(2), SpinlockMutex::new(3)];
+
+    let mut guard = SpinlockMutex::lock_all(&channels);
+    assert_eq!(guard.len(), 3);
+    guard[0] = 10;
+    guard.iter_mut().for_each(|channel| *channel *= 2);
+    assert!(channels[1].try_lock().is_none());
+    assert_eq!(guard.get(3), None);
+    assert_eq!(guard.iter().copied().collect::<Vec<_>>(), [20, 4, 6]);
+    drop(guard);
+
+    assert_eq!(channels[2].try_lock().as_deref(), Some(&6));
+    assert!(SpinlockMutex::lock_all(&channels[..0]).is_empty());
+}
+
+#[test]
+fn large_const_initializers_are_evaluated_at_compile_time() {
+    const fn squares() -> [u32; 1024] {
+        let mut table = [0; 1024];
+        let mut i = 0;
+        while i < table.len() {
+            table[i] = (i * i) as u32;
+            i += 1;
+        }
+        table
+    }
+    static SQUARES: SpinlockMutex<18, [u32; 1024]> = SpinlockMutex::new(squares());
+
+    let squares = SQUARES.lock();
+    assert_eq!((squares[0], squares[3], squares[1023]), (0, 9, 1023 * 1023));
+}
+
+#[test]
+fn iteration_through_the_guard_holds_the_lock_for_the_whole_loop() {
+    static SAMPLES: SpinlockMutex<19, [u16; 64]> = SpinlockMutex::new([0; 64]);
+
+    let writer = std::thread::spawn(|| {
+        for round in 1..=100 {
+            let mut samples = SAMPLES.lock();
+            for sample in samples.iter_mut() {
+                *sample = round;
+            }
+        }
+    });
+    for _ in 0..100 {
+        let samples = SAMPLES.lock();
+        assert!(samples.iter().all(|&sample| sample == samples[0]));
+    }
+    writer.join().unwrap();
+
+    for sample in &mut *SAMPLES.lock() {
+        *sample += 1;
+    }
+    assert!(SAMPLES.lock().iter().all(|&sample| sample == 101));
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn heapless_vec_iterates_through_the_guard() {
+    let queue: SpinlockMutex<20, heapless::Vec<u8, 8>> = SpinlockMutex::new(heapless::Vec::new());
+
+    let mut guard = queue.lock();
+    guard.extend_from_slice(&[1, 2, 3]).unwrap();
+    guard.iter_mut().for_each(|byte| *byte *= 10);
+    assert!(queue.try_lock().is_none());
+    assert_eq!(guard.iter().copied().collect::<Vec<_>>(), [10, 20, 30]);
+}
+
+#[test]
+fn lock_as_releases_the_lock_if_the_projection_fails() {
+    #[derive(Debug, PartialEq)]
+    enum State {
+        Idle,
+        Running { progress: u32 },
+    }
+    fn running(state: &mut State) -> Option<&mut u32> {
+        match state {
+            State::Running { progress } => Some(progress),
+            State::Idle => None,
+        }
+    }
+    static STATE: SpinlockMutex<21, State> = SpinlockMutex::new(State::Idle);
+
+    let holder = std::thread::spawn(|| STATE.lock_as(running).is_some());
+    assert!(!holder.join().unwrap());
+    assert_eq!(STATE.try_lock().as_deref(), Some(&State::Idle));
+
+    *STATE.lock() = State::Running { progress: 1 };
+    let mut progress = STATE.lock_as(running).unwrap();
+    *progress += 1;
+    assert!(STATE.try_lock().is_none());
+    drop(progress);
+    assert_eq!(*STATE.lock(), State::Running { progress: 2 });
+}
+
+#[test]
+fn irq_shared_mutexes_lock_in_critical_sections() {
+    static EVENTS: SpinlockMutex<22, u32, IrqShared> = SpinlockMutex::new_irq_shared(0);
+
+    // SAFETY: Stands in for the token of `cortex_m::interrupt::free`, which needs the hardware.
+    let cs = unsafe { cortex_m::interrupt::CriticalSection::new() };
+    let mut events = EVENTS.lock_in_free(&cs);
+    *events += 1;
+    assert!(EVENTS.try_lock(&cs).is_none());
+    drop(events);
+
+    assert_eq!(EVENTS.try_lock(&cs).as_deref(), Some(&1));
+}
+
+#[test]
+fn cell_values_are_shared_across_cores() {
+    static VALUE: SpinlockCell<23, u32> = SpinlockCell::new(1);
+
+    let core1 = std::thread::spawn(|| {
+        #[cfg(feature = "std-testing")]
+        CoreId::Core1.emulate();
+        assert_eq!(VALUE.get(), 1);
+        VALUE.set(2);
+        VALUE.replace(3)
+    });
+
+    assert_eq!(core1.join().unwrap(), 2);
+    assert_eq!(VALUE.replace(4), 3);
+    assert_eq!(VALUE.get(), 4);
+}
+
+#[test]
+fn cell_updates_from_both_cores_are_not_lost() {
+    static COUNTER: SpinlockCell<24, u32> = SpinlockCell::new(0);
+
+    let core1 = std::thread::spawn(|| {
+        #[cfg(feature = "std-testing")]
+        CoreId::Core1.emulate();
+        for _ in 0..10_000 {
+            COUNTER.update(|n| n + 1);
+        }
+    });
+    for _ in 0..10_000 {
+        COUNTER.update(|n| n + 1);
+    }
+    core1.join().unwrap();
+
+    assert_eq!(COUNTER.get(), 20_000);
+}
+
+#[cfg(feature = "heapless")]
+#[test]
+fn drain_into_moves_what_fits_and_keeps_the_rest_queued() {
+    use heapless::Vec;
+
+    let queue: SpinlockMutex<25, Vec<String, 8>> = SpinlockMutex::new(Vec::new());
+    let item = |n: u32| n.to_string();
+    for n in 0..5 {
+        queue.lock().push(item(n)).unwrap();
+    }
+
+    // A full drain empties the queue
+    let mut all: Vec<String, 8> = Vec::new();
+    assert_eq!(queue.drain_into(&mut all), 5);
+    assert_eq!(all, [item(0), item(1), item(2), item(3), item(4)]);
+    assert!(queue.lock().is_empty());
+
+    // A partial drain moves elements from the front until the destination is full
+    for n in 5..10 {
+        queue.lock().push(item(n)).unwrap();
+    }
+    let mut batch: Vec<String, 3> = Vec::new();
+    batch.push(item(99)).unwrap();
+    assert_eq!(queue.drain_into(&mut batch), 2);
+    assert_eq!(batch, [item(99), item(5), item(6)]);
+    assert_eq!(queue.drain_into(&mut batch), 0);
+
+    // The rest stays queued in order, and the queue keeps working
+    queue.lock().push(item(10)).unwrap();
+    assert_eq!(*queue.lock(), [item(7), item(8), item(9), item(10)]);
+    let mut rest: Vec<String, 8> = Vec::new();
+    assert_eq!(queue.drain_into(&mut rest), 4);
+    assert_eq!(rest, [item(7), item(8), item(9), item(10)]);
+}
+
+#[test]
+fn scoped_set_restores_the_previous_value_at_the_end_of_the_scope() {
+    let level: SpinlockMutex<26, u8> = SpinlockMutex::new(1);
+
+    {
+        let guard = level.scoped_set(3);
+        assert_eq!(*guard.previous(), 1);
+        assert_eq!(*level.lock(), 3);
+        *level.lock() = 4;
+    }
+
+    assert_eq!(*level.lock(), 1);
+}
+
+#[test]
+fn scoped_set_restores_the_previous_value_on_unwind() {
+    let level: SpinlockMutex<27, u8> = SpinlockMutex::new(1);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _guard = level.scoped_set(3);
+        assert_eq!(*level.lock(), 3);
+        panic!("scope failed");
+    }));
+
+    assert!(result.is_err());
+    assert_eq!(*level.lock(), 1);
+}
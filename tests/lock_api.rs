@@ -0,0 +1,41 @@
+//! Host tests of the `lock_api` integration.
+#![cfg(feature = "lock_api")]
+
+use lock_api::RawMutex;
+use rp_spinlockmutex::{LockApiMutex, LockApiMutexGuard, RawSpinlock, SpinlockMutex};
+
+#[test]
+fn raw_spinlock_locks_and_unlocks() {
+    let raw = RawSpinlock::<0>::INIT;
+
+    raw.lock();
+    assert!(raw.is_locked());
+    assert!(!raw.try_lock());
+
+    // SAFETY: The lock is held by this test.
+    unsafe { raw.unlock() };
+    assert!(!raw.is_locked());
+    assert!(raw.try_lock());
+    unsafe { raw.unlock() };
+}
+
+#[test]
+fn mapped_guard_keeps_the_lock() {
+    let mutex: LockApiMutex<1, (i32, i32)> = LockApiMutex::new((0, 0));
+
+    let mut y = LockApiMutexGuard::map(mutex.lock(), |(_, y)| y);
+    *y = 2;
+    assert!(mutex.try_lock().is_none());
+    drop(y);
+
+    assert_eq!(*mutex.lock(), (0, 2));
+}
+
+#[test]
+fn contends_with_spinlock_mutex() {
+    let a: LockApiMutex<2, i32> = LockApiMutex::new(0);
+    let b: SpinlockMutex<2, i32> = SpinlockMutex::new(0);
+
+    let _guard = a.lock();
+    assert!(b.try_lock().is_none());
+}
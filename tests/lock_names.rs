@@ -0,0 +1,27 @@
+//! Host tests of the spinlock name table. The table is global, so this binary has a single test.
+
+use rp_spinlockmutex::diag::{dump_held_locks, lock_name, set_lock_names};
+use rp_spinlockmutex::SpinlockMutex;
+
+static LOCK_NAMES: [&str; 32] = {
+    let mut names = [""; 32];
+    names[3] = "sensor_state";
+    names
+};
+
+#[test]
+fn held_locks_are_listed_with_their_names() {
+    assert_eq!(lock_name(3), None);
+    set_lock_names(&LOCK_NAMES);
+    assert_eq!(lock_name(3), Some("sensor_state"));
+    assert_eq!(lock_name(4), None);
+    assert_eq!(lock_name(32), None);
+
+    let named: SpinlockMutex<3, i32> = SpinlockMutex::new(0);
+    let unnamed: SpinlockMutex<4, i32> = SpinlockMutex::new(0);
+    let _guards = (named.lock(), unnamed.lock());
+
+    let mut dump = String::new();
+    dump_held_locks(&mut dump).unwrap();
+    assert_eq!(dump, "held spinlocks: 3 ('sensor_state'), 4");
+}
@@ -0,0 +1,30 @@
+//! Host tests of `OrderedSpinlockMutex`. All host threads count as core 0 and share the held
+//! orders, so this binary has a single test.
+#![cfg(debug_assertions)]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use rp_spinlockmutex::OrderedSpinlockMutex;
+
+#[test]
+fn locking_against_the_order_panics() {
+    let config: OrderedSpinlockMutex<0, 0, i32> = OrderedSpinlockMutex::new(0);
+    let state: OrderedSpinlockMutex<1, 5, i32> = OrderedSpinlockMutex::new(0);
+
+    let (guard, mut inner) = (config.lock(), state.lock());
+    *inner = *guard + 1;
+    drop((guard, inner));
+
+    let guard = state.lock();
+    let inversion = catch_unwind(AssertUnwindSafe(|| config.try_lock().is_some()));
+    let message = *inversion.unwrap_err().downcast::<String>().unwrap();
+    assert_eq!(
+        message,
+        "lock order violation: SpinlockMutex<0, _> of order 0 locked while holding order 5"
+    );
+    assert!(catch_unwind(AssertUnwindSafe(|| drop(state.lock()))).is_err());
+    drop(guard);
+
+    // Releasing restores the held orders
+    let _guards = (config.lock(), state.lock());
+}
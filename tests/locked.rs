@@ -0,0 +1,27 @@
+//! Host tests of `locked!`.
+
+use rp_spinlockmutex::{locked, FairSpinlockMutex, SpinlockMutex};
+
+#[test]
+fn locked_releases_after_the_block() {
+    let mutex: SpinlockMutex<0, (u32, u32)> = SpinlockMutex::new((0, 0));
+
+    let sum = locked!(mutex => |(x, y)| {
+        *x += 1;
+        *y += 2;
+        assert!(mutex.try_lock().is_none());
+        *x + *y
+    });
+
+    assert_eq!(sum, 3);
+    assert_eq!(mutex.try_lock().as_deref(), Some(&(1, 2)));
+}
+
+#[test]
+fn locked_accepts_other_mutexes_of_the_crate() {
+    static COUNTER: FairSpinlockMutex<1, u32> = FairSpinlockMutex::new(0);
+
+    locked!(COUNTER => |counter| *counter += 1);
+
+    assert_eq!(*COUNTER.lock(), 1);
+}
@@ -0,0 +1,83 @@
+//! Model checks of the guards' memory ordering with [loom](https://github.com/tokio-rs/loom).
+//!
+//! Under `--cfg loom`, the host locks do not order memory accesses, like the hardware
+//! spinlocks, and loom explores all interleavings of the threads below, checking that the
+//! fences of the guards make every access to the data happen after the previous holder's.
+//!
+//! ```sh
+//! RUSTFLAGS="--cfg loom" cargo test --release --target x86_64-unknown-linux-gnu --test loom
+//! ```
+#![cfg(loom)]
+
+use loom::cell::UnsafeCell;
+use loom::sync::Arc;
+use loom::thread;
+
+use rp_spinlockmutex::SpinlockMutex;
+
+#[test]
+fn lock_orders_the_accesses_of_both_threads() {
+    loom::model(|| {
+        let mutex: Arc<SpinlockMutex<0, UnsafeCell<u32>>> = Arc::new(SpinlockMutex::new(UnsafeCell::new(0)));
+
+        let other = {
+            let mutex = mutex.clone();
+            // SAFETY: The cell is only accessed while the mutex is locked.
+            thread::spawn(move || mutex.lock().with_mut(|value| unsafe { *value += 1 }))
+        };
+        // SAFETY: As above.
+        mutex.lock().with_mut(|value| unsafe { *value += 1 });
+        other.join().unwrap();
+
+        // SAFETY: As above.
+        assert_eq!(mutex.lock().with(|value| unsafe { *value }), 2);
+    });
+}
+
+#[test]
+fn try_lock_is_mutually_exclusive() {
+    loom::model(|| {
+        let mutex: Arc<SpinlockMutex<1, UnsafeCell<u32>>> = Arc::new(SpinlockMutex::new(UnsafeCell::new(0)));
+
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let mutex = mutex.clone();
+                thread::spawn(move || {
+                    if let Some(guard) = mutex.try_lock() {
+                        // SAFETY: The cell is only accessed while the mutex is locked.
+                        guard.with_mut(|value| unsafe { *value += 1 });
+                    }
+                })
+            })
+            .collect();
+        threads.into_iter().for_each(|thread| thread.join().unwrap());
+
+        // SAFETY: As above.
+        let value = mutex.lock().with(|value| unsafe { *value });
+        assert!((1..=2).contains(&value));
+    });
+}
+
+#[test]
+fn bump_hands_the_lock_over_without_a_race() {
+    loom::model(|| {
+        let mutex: Arc<SpinlockMutex<2, UnsafeCell<u32>>> = Arc::new(SpinlockMutex::new(UnsafeCell::new(0)));
+
+        let other = {
+            let mutex = mutex.clone();
+            // SAFETY: The cell is only accessed while the mutex is locked.
+            thread::spawn(move || mutex.lock().with_mut(|value| unsafe { *value += 10 }))
+        };
+        let mut guard = mutex.lock();
+        // SAFETY: As above.
+        guard.with_mut(|value| unsafe { *value += 1 });
+        guard.bump();
+        // SAFETY: As above.
+        guard.with_mut(|value| unsafe { *value += 1 });
+        drop(guard);
+        other.join().unwrap();
+
+        // SAFETY: As above.
+        assert_eq!(mutex.lock().with(|value| unsafe { *value }), 12);
+    });
+}
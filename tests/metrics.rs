@@ -0,0 +1,90 @@
+//! Host tests of the lock statistics.
+#![cfg(feature = "metrics")]
+
+use rp_spinlockmutex::diag::{lock_stats, print_lock_summary, LockStats};
+use rp_spinlockmutex::spin::BoundedRetries;
+use rp_spinlockmutex::SpinlockMutex;
+
+#[test]
+fn acquisitions_are_counted() {
+    let mutex: SpinlockMutex<0, i32> = SpinlockMutex::new(0);
+
+    *mutex.lock() += 1;
+    *mutex.try_lock().unwrap() += 1;
+
+    assert_eq!(
+        lock_stats(0),
+        LockStats {
+            acquisitions: 2,
+            contentions: 0,
+            max_spins: 0
+        }
+    );
+}
+
+#[test]
+fn contention_is_counted() {
+    static MUTEX: SpinlockMutex<1, i32> = SpinlockMutex::new(0);
+
+    let guard = MUTEX.lock();
+    let waiter = std::thread::spawn(|| *MUTEX.lock() += 1);
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    drop(guard);
+    waiter.join().unwrap();
+
+    let stats = lock_stats(1);
+    assert_eq!((stats.acquisitions, stats.contentions), (2, 1));
+    assert!(stats.max_spins > 0);
+}
+
+#[test]
+fn summary_lists_acquired_locks() {
+    let mutex: SpinlockMutex<2, i32> = SpinlockMutex::new(0);
+    *mutex.lock() += 1;
+
+    let mut summary = String::new();
+    print_lock_summary(&mut summary).unwrap();
+
+    assert!(summary.starts_with("lock  acquisitions  contentions   max spins\n"));
+    assert!(summary.contains("\n   2             1            0           0\n"));
+    assert!(!summary.contains("\n  31 "));
+}
+
+#[test]
+fn stats_can_be_reset() {
+    let mutex: SpinlockMutex<3, i32> = SpinlockMutex::new(0);
+    let other: SpinlockMutex<4, i32> = SpinlockMutex::new(0);
+
+    *mutex.lock() += 1;
+    *other.lock() += 1;
+    mutex.reset_stats();
+    assert_eq!(lock_stats(3), LockStats::default());
+    assert_eq!(lock_stats(4).acquisitions, 1);
+
+    *mutex.lock() += 1;
+    assert_eq!(lock_stats(3).acquisitions, 1);
+}
+
+#[test]
+fn contention_of_retrying_locks_is_counted() {
+    static MUTEX: SpinlockMutex<5, i32> = SpinlockMutex::new(0);
+
+    fn contend(waiter: fn()) {
+        let guard = MUTEX.lock();
+        let waiter = std::thread::spawn(waiter);
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        drop(guard);
+        waiter.join().unwrap();
+    }
+    contend(|| *MUTEX.lock_or_abort(|| false).unwrap() += 1);
+    contend(|| *MUTEX.lock_with_strategy(&mut BoundedRetries(u32::MAX)).unwrap() += 1);
+    // Aborted acquisitions are not counted.
+    let guard = MUTEX.lock();
+    let aborted = std::thread::spawn(|| MUTEX.lock_or_abort(|| true).is_none());
+    assert!(aborted.join().unwrap());
+    drop(guard);
+
+    let stats = lock_stats(5);
+    assert_eq!((stats.acquisitions, stats.contentions), (5, 2));
+    assert!(stats.max_spins > 0);
+}
@@ -0,0 +1,86 @@
+//! Tests of the core guard semantics, sized to run under Miri, which checks the unsafe code of
+//! the crate for undefined behavior such as aliasing violations:
+//!
+//! ```sh
+//! cargo +nightly miri test --target x86_64-unknown-linux-gnu --test miri
+//! ```
+//!
+//! The other host tests run under Miri as well, but take much longer.
+
+use std::thread;
+
+use rp_spinlockmutex::{spinlock_pool, SpinlockMutex, SpinlockMutexGuard};
+
+#[test]
+fn guards_hand_out_exclusive_references() {
+    let mutex: SpinlockMutex<0, [u32; 4]> = SpinlockMutex::new([0; 4]);
+
+    let mut guard = mutex.lock();
+    let shared: &[u32; 4] = &guard;
+    assert_eq!(shared[0], 0);
+    guard[1] = 1;
+    SpinlockMutexGuard::get_mut(&mut guard)[2] = 2;
+    // SAFETY: The guard is alive and no reference into the data is used while writing.
+    unsafe { (*SpinlockMutexGuard::as_mut_ptr(&mut guard))[3] = 3 };
+    drop(guard);
+
+    assert_eq!(*mutex.lock(), [0, 1, 2, 3]);
+}
+
+#[test]
+fn guards_synchronize_threads() {
+    static COUNTER: SpinlockMutex<1, u64> = SpinlockMutex::new(0);
+
+    let threads: Vec<_> = (0..2)
+        .map(|_| {
+            thread::spawn(|| {
+                for _ in 0..10 {
+                    *COUNTER.lock() += 1;
+                }
+            })
+        })
+        .collect();
+    threads.into_iter().for_each(|thread| thread.join().unwrap());
+
+    assert_eq!(*COUNTER.lock(), 20);
+}
+
+#[test]
+fn guards_can_be_sent_to_and_shared_with_other_threads() {
+    static DATA: SpinlockMutex<2, Vec<u8>> = SpinlockMutex::new(Vec::new());
+
+    let guard = DATA.lock();
+    let mut guard = thread::spawn(move || {
+        assert!(guard.is_empty());
+        guard
+    })
+    .join()
+    .unwrap();
+    guard.push(1);
+    drop(guard);
+
+    assert_eq!(*DATA.lock(), [1]);
+}
+
+#[test]
+fn mapped_and_pool_guards_stay_valid() {
+    spinlock_pool! {
+        struct Slots: (u8, u16) = (0, 0);
+        spinlocks [3, 4];
+    }
+
+    let slots = Slots::new();
+    slots.lock_slot(1).1 = 7;
+    assert_eq!(*slots.try_lock_any().unwrap(), (0, 0));
+    assert_eq!(*slots.lock_slot(1), (0, 7));
+
+    let mutex: SpinlockMutex<5, (u8, [u8; 4])> = SpinlockMutex::new((0, [0; 4]));
+    let mut array = SpinlockMutexGuard::map(mutex.lock(), |(_, array)| array);
+    array[1] = 1;
+    drop(array);
+    assert_eq!(*mutex.lock(), (0, [0, 1, 0, 0]));
+
+    let buffer: SpinlockMutex<6, [u8; 8]> = SpinlockMutex::new([0; 8]);
+    buffer.lock_range(2..4).fill(1);
+    assert_eq!(*buffer.lock(), [0, 0, 1, 1, 0, 0, 0, 0]);
+}
@@ -0,0 +1,42 @@
+//! Host tests of `PinnedSpinlockMutex` with a `!Unpin` payload.
+
+use core::marker::PhantomPinned;
+use core::pin::Pin;
+
+use rp_spinlockmutex::PinnedSpinlockMutex;
+
+struct Node {
+    value: u32,
+    _pin: PhantomPinned,
+}
+
+static NODE: PinnedSpinlockMutex<4, Node> = PinnedSpinlockMutex::new(Node {
+    value: 0,
+    _pin: PhantomPinned,
+});
+
+#[test]
+fn data_does_not_move_between_locks() {
+    let mutex = Pin::static_ref(&NODE);
+
+    let mut node = mutex.lock();
+    let address: *const Node = &*node;
+    // SAFETY: `value` is not structurally pinned.
+    unsafe { node.as_mut().get_unchecked_mut().value = 42 };
+    drop(node);
+
+    let node = mutex.lock();
+    assert_eq!(&*node as *const Node, address);
+    assert_eq!(node.value, 42);
+}
+
+#[test]
+fn try_lock_fails_while_locked() {
+    let mutex = Box::pin(PinnedSpinlockMutex::<5, Node>::new(Node {
+        value: 0,
+        _pin: PhantomPinned,
+    }));
+
+    let _node = mutex.as_ref().lock();
+    assert!(mutex.as_ref().try_lock().is_none());
+}
@@ -0,0 +1,57 @@
+//! Host tests of `spinlock_pool!`.
+
+use rp_spinlockmutex::spinlock_pool;
+
+spinlock_pool! {
+    struct Counters: u32 = 0;
+    spinlocks [3, 4, 5];
+}
+
+spinlock_pool! {
+    struct Buffers: [u8; 4] = [0; 4];
+    spinlocks [6, 7];
+}
+
+#[test]
+fn slots_use_their_own_spinlocks() {
+    let counters = Counters::new();
+    assert_eq!(Counters::LEN, 3);
+
+    let first = counters.lock_slot(0);
+    let mut last = counters.lock_slot(2);
+    *last += 1;
+
+    assert_eq!(first.spinlock_number(), 3);
+    assert_eq!(last.spinlock_number(), 5);
+    assert!(counters.try_lock_slot(0).is_none());
+    assert_eq!(counters.try_lock_slot(1).as_deref(), Some(&0));
+
+    drop(last);
+    assert_eq!(counters.try_lock_slot(2).as_deref(), Some(&1));
+    drop(first);
+}
+
+#[test]
+fn try_lock_any_returns_free_slots() {
+    let buffers = Buffers::new();
+
+    let mut first = buffers.try_lock_any().unwrap();
+    first[0] = 1;
+    let second = buffers.try_lock_any().unwrap();
+    assert_eq!((first.spinlock_number(), second.spinlock_number()), (6, 7));
+    assert!(buffers.try_lock_any().is_none());
+
+    drop(first);
+    assert_eq!(buffers.try_lock_any().as_deref(), Some(&[1, 0, 0, 0]));
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn lock_slot_panics_out_of_bounds() {
+    spinlock_pool! {
+        struct Single: () = ();
+        spinlocks [8];
+    }
+
+    let _guard = Single::new().lock_slot(1);
+}
@@ -0,0 +1,43 @@
+//! Host tests of `SpinlockMutex::lock_probe`.
+#![cfg(feature = "embedded-hal")]
+
+use std::cell::RefCell;
+use std::convert::Infallible;
+
+use embedded_hal::digital::v2::OutputPin;
+use rp_spinlockmutex::SpinlockMutex;
+
+/// Records its levels, and whether the mutex was locked at each change.
+struct RecordingPin<'a> {
+    mutex: &'a SpinlockMutex<0, i32>,
+    levels: &'a RefCell<Vec<(bool, bool)>>,
+}
+
+impl OutputPin for RecordingPin<'_> {
+    type Error = Infallible;
+
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.levels.borrow_mut().push((false, self.mutex.try_lock().is_none()));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.levels.borrow_mut().push((true, self.mutex.try_lock().is_none()));
+        Ok(())
+    }
+}
+
+#[test]
+fn pin_is_high_exactly_while_locked() {
+    let mutex = SpinlockMutex::new(0);
+    let levels = RefCell::new(Vec::new());
+    let mut pin = RecordingPin {
+        mutex: &mutex,
+        levels: &levels,
+    };
+
+    *mutex.lock_probe(&mut pin) += 1;
+
+    assert_eq!(*levels.borrow(), [(true, true), (false, true)]);
+    assert_eq!(mutex.try_lock().as_deref(), Some(&1));
+}
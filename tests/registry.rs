@@ -0,0 +1,55 @@
+//! Host tests of the `registry` feature, which is only compiled into debug builds.
+#![cfg(all(feature = "registry", debug_assertions))]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use rp_spinlockmutex::SpinlockMutex;
+
+static COUNTER: SpinlockMutex<0, u32> = SpinlockMutex::new(0);
+
+static CHANNELS: [SpinlockMutex<1, u16>; 3] = [SpinlockMutex::new(0), SpinlockMutex::new(1), SpinlockMutex::new(2)];
+
+static COUNT: SpinlockMutex<3, u32> = SpinlockMutex::new(0);
+static NAME: SpinlockMutex<3, &str> = SpinlockMutex::new("");
+
+#[test]
+fn views_of_the_same_mutex_are_not_reported() {
+    fn increment() -> u32 {
+        let mut count = COUNTER.lock();
+        *count += 1;
+        *count
+    }
+    let callback: SpinlockMutex<2, fn() -> u32> = SpinlockMutex::new(increment);
+    let view: &SpinlockMutex<2, dyn FnMut() -> u32 + Send> = &callback;
+
+    (callback.lock())();
+    assert_eq!((view.lock())(), 2);
+    assert_eq!(*COUNTER.lock(), 2);
+}
+
+#[test]
+fn mutexes_of_the_same_type_may_share_the_spinlock() {
+    for (n, channel) in CHANNELS.iter().enumerate() {
+        assert_eq!(*channel.lock(), n as u16);
+    }
+    assert_eq!(SpinlockMutex::lock_all(&CHANNELS[1..]).len(), 2);
+
+    // Mutexes on the stack move and are created again.
+    for n in 0..2 {
+        let local: SpinlockMutex<4, u32> = SpinlockMutex::new(n);
+        let moved = local;
+        assert_eq!(*moved.lock(), n);
+    }
+}
+
+#[test]
+fn mutexes_of_different_types_are_reported() {
+    drop(COUNT.lock());
+
+    let conflict = catch_unwind(AssertUnwindSafe(|| drop(NAME.lock())));
+    let message = *conflict.unwrap_err().downcast::<String>().unwrap();
+    assert_eq!(message, "spinlock 3 is used by mutexes for both `u32` and `&str`");
+
+    // The conflicting lock was released during the unwind.
+    assert!(COUNT.try_lock().is_some());
+}
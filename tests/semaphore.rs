@@ -0,0 +1,65 @@
+//! Host tests of `SpinlockSemaphore`.
+
+use rp_spinlockmutex::SpinlockSemaphore;
+
+#[test]
+fn acquiring_and_releasing_counts_the_permits() {
+    let semaphore: SpinlockSemaphore<0> = SpinlockSemaphore::new(3);
+
+    let first = semaphore.acquire();
+    let second = semaphore.try_acquire().unwrap();
+    assert_eq!(semaphore.available(), 1);
+
+    first.release();
+    assert_eq!(semaphore.available(), 2);
+    drop(second);
+    assert_eq!(semaphore.available(), 3);
+}
+
+#[test]
+fn try_acquire_fails_without_permits() {
+    let semaphore: SpinlockSemaphore<1> = SpinlockSemaphore::new(1);
+
+    let permit = semaphore.acquire();
+    assert!(semaphore.try_acquire().is_none());
+    assert_eq!(semaphore.available(), 0);
+
+    drop(permit);
+    assert!(semaphore.try_acquire().is_some());
+
+    let empty: SpinlockSemaphore<2> = SpinlockSemaphore::new(0);
+    assert!(empty.try_acquire().is_none());
+}
+
+#[test]
+fn releasing_never_exceeds_the_initial_permits() {
+    let semaphore: SpinlockSemaphore<3> = SpinlockSemaphore::new(2);
+
+    for _ in 0..10 {
+        let permits = [semaphore.acquire(), semaphore.acquire()];
+        assert!(semaphore.try_acquire().is_none());
+        drop(permits);
+        assert_eq!(semaphore.available(), 2);
+    }
+
+    // A forgotten permit is never returned.
+    std::mem::forget(semaphore.acquire());
+    assert_eq!(semaphore.available(), 1);
+}
+
+#[test]
+fn acquire_waits_for_a_permit_released_elsewhere() {
+    static IN_FLIGHT: SpinlockSemaphore<4> = SpinlockSemaphore::new(1);
+
+    let permit = IN_FLIGHT.acquire();
+    let waiter = std::thread::spawn(|| {
+        let _permit = IN_FLIGHT.acquire();
+        IN_FLIGHT.available()
+    });
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    assert!(!waiter.is_finished());
+    drop(permit);
+
+    assert_eq!(waiter.join().unwrap(), 0);
+    assert_eq!(IN_FLIGHT.available(), 1);
+}
@@ -0,0 +1,68 @@
+//! Host tests of `SpinlockSeqLock`.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rp_spinlockmutex::SpinlockSeqLock;
+
+#[test]
+fn read_returns_the_last_write() {
+    let seqlock: SpinlockSeqLock<0, u64> = SpinlockSeqLock::new(1);
+
+    assert_eq!(seqlock.read(), 1);
+    seqlock.write(|value| *value += 41);
+    assert_eq!(seqlock.read(), 42);
+}
+
+#[test]
+fn a_panicking_write_still_ends() {
+    let seqlock: SpinlockSeqLock<2, u64> = SpinlockSeqLock::new(1);
+
+    let writing = catch_unwind(AssertUnwindSafe(|| {
+        seqlock.write(|value| {
+            *value = 2;
+            panic!("sensor failed");
+        })
+    }));
+
+    assert!(writing.is_err());
+    assert_eq!(seqlock.read(), 2);
+    seqlock.write(|value| *value += 1);
+    assert_eq!(seqlock.read(), 3);
+}
+
+// The readers copy the value racily by design, which is outside of what Miri accepts.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn concurrent_reads_never_observe_a_torn_value() {
+    static VALUES: SpinlockSeqLock<1, [u64; 8]> = SpinlockSeqLock::new([0; 8]);
+    static DONE: AtomicBool = AtomicBool::new(false);
+
+    let writer = std::thread::spawn(|| {
+        for round in 1..=10_000 {
+            VALUES.write(|values| values.iter_mut().for_each(|value| *value = round));
+        }
+        DONE.store(true, Ordering::Relaxed);
+    });
+    let readers: Vec<_> = (0..2)
+        .map(|_| {
+            std::thread::spawn(|| {
+                let mut last = 0;
+                while !DONE.load(Ordering::Relaxed) {
+                    let values = VALUES.read();
+                    assert!(
+                        values.iter().all(|&value| value == values[0]),
+                        "torn read: {:?}",
+                        values
+                    );
+                    assert!(values[0] >= last, "reads went back in time");
+                    last = values[0];
+                }
+            })
+        })
+        .collect();
+
+    writer.join().unwrap();
+    readers.into_iter().for_each(|reader| reader.join().unwrap());
+    assert_eq!(VALUES.read(), [10_000; 8]);
+}
@@ -0,0 +1,69 @@
+//! An example of testing application logic built on `SpinlockMutex` on the host.
+//!
+//! The `Telemetry` store below is written exactly as it would be in firmware: a `static`
+//! mutex shared between the cores. On the host, the two "cores" are threads. Run with
+//! `cargo test --target x86_64-unknown-linux-gnu --tests --features std-testing` to have
+//! contending threads sleep instead of spin.
+
+use std::thread;
+
+use rp_spinlockmutex::SpinlockMutex;
+
+/// The latest sensor readings, published by one core and consumed by the other.
+struct Telemetry {
+    samples: u32,
+    sum: u64,
+    max: u16,
+}
+
+static TELEMETRY: SpinlockMutex<20, Telemetry> = SpinlockMutex::new(Telemetry {
+    samples: 0,
+    sum: 0,
+    max: 0,
+});
+
+fn publish(reading: u16) {
+    let mut telemetry = TELEMETRY.lock();
+    telemetry.samples += 1;
+    telemetry.sum += u64::from(reading);
+    telemetry.max = telemetry.max.max(reading);
+}
+
+/// Returns the number of samples, their mean and maximum, and starts a new period.
+fn take_summary() -> (u32, u64, u16) {
+    let mut telemetry = TELEMETRY.lock();
+    let summary = (
+        telemetry.samples,
+        telemetry.sum.checked_div(u64::from(telemetry.samples)).unwrap_or(0),
+        telemetry.max,
+    );
+    *telemetry = Telemetry {
+        samples: 0,
+        sum: 0,
+        max: 0,
+    };
+    summary
+}
+
+// A single test, since the tests of a binary run in parallel but share `TELEMETRY`.
+#[test]
+fn summaries_account_for_every_reading_of_both_cores() {
+    const READINGS: u16 = 1_000;
+
+    [3, 9, 6].into_iter().for_each(publish);
+    assert_eq!(take_summary(), (3, 6, 9));
+    assert_eq!(take_summary(), (0, 0, 0));
+
+    let cores: Vec<_> = (0..2)
+        .map(|core| thread::spawn(move || (0..READINGS).for_each(|i| publish(i * 2 + core))))
+        .collect();
+
+    let mut samples = 0;
+    while cores.iter().any(|core| !core.is_finished()) {
+        samples += take_summary().0;
+    }
+    cores.into_iter().for_each(|core| core.join().unwrap());
+    samples += take_summary().0;
+
+    assert_eq!(samples, 2 * u32::from(READINGS));
+}
@@ -0,0 +1,94 @@
+//! Host tests of `SpinlockMutex::lock_with_strategy` and the built-in strategies.
+
+use rp_spinlockmutex::spin::{BoundedRetries, Busy, ExponentialBackoff, SpinAction, SpinStrategy};
+use rp_spinlockmutex::SpinlockMutex;
+
+/// Records the attempts it is called with, aborting after `limit` calls.
+struct Recording {
+    attempts: Vec<u32>,
+    limit: usize,
+}
+
+impl SpinStrategy for Recording {
+    fn on_spin(&mut self, attempt: u32) -> SpinAction {
+        self.attempts.push(attempt);
+        if self.attempts.len() < self.limit {
+            SpinAction::Retry
+        } else {
+            SpinAction::Abort
+        }
+    }
+}
+
+#[test]
+fn strategy_is_not_called_if_the_lock_is_free() {
+    let mutex: SpinlockMutex<0, i32> = SpinlockMutex::new(0);
+    let mut strategy = Recording {
+        attempts: Vec::new(),
+        limit: 1,
+    };
+
+    assert!(mutex.lock_with_strategy(&mut strategy).is_some());
+    assert!(strategy.attempts.is_empty());
+}
+
+#[test]
+fn strategy_is_called_with_increasing_attempts() {
+    let mutex: SpinlockMutex<1, i32> = SpinlockMutex::new(0);
+    let mut strategy = Recording {
+        attempts: Vec::new(),
+        limit: 4,
+    };
+
+    let _guard = mutex.lock();
+    assert!(mutex.lock_with_strategy(&mut strategy).is_none());
+    assert_eq!(strategy.attempts, [0, 1, 2, 3]);
+}
+
+#[test]
+fn bounded_retries_aborts_after_the_limit() {
+    let mut strategy = BoundedRetries(3);
+    let actions: Vec<_> = (0..5).map(|attempt| strategy.on_spin(attempt)).collect();
+    assert_eq!(
+        actions,
+        [
+            SpinAction::Retry,
+            SpinAction::Retry,
+            SpinAction::Retry,
+            SpinAction::Abort,
+            SpinAction::Abort
+        ]
+    );
+
+    let mutex: SpinlockMutex<2, i32> = SpinlockMutex::new(0);
+    let _guard = mutex.lock();
+    assert!(mutex.lock_with_strategy(&mut BoundedRetries(3)).is_none());
+}
+
+#[test]
+fn busy_and_backoff_always_retry() {
+    for attempt in [0, 1, 10, u32::MAX] {
+        assert_eq!(Busy.on_spin(attempt), SpinAction::Retry);
+        assert_eq!(
+            ExponentialBackoff { max_exponent: 4 }.on_spin(attempt),
+            SpinAction::Retry
+        );
+    }
+}
+
+#[test]
+fn backoff_acquires_once_the_lock_is_free() {
+    static MUTEX: SpinlockMutex<3, i32> = SpinlockMutex::new(0);
+
+    let guard = MUTEX.lock();
+    let waiter = std::thread::spawn(|| {
+        *MUTEX
+            .lock_with_strategy(&mut ExponentialBackoff { max_exponent: 8 })
+            .unwrap() += 1;
+    });
+    std::thread::sleep(std::time::Duration::from_millis(10));
+    drop(guard);
+    waiter.join().unwrap();
+
+    assert_eq!(*MUTEX.lock(), 1);
+}
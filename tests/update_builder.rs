@@ -0,0 +1,40 @@
+//! Host tests of `SpinlockMutex::update_builder`, counting lock acquisitions through the trace hook.
+#![cfg(feature = "trace")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rp_spinlockmutex::{set_trace_hook, LockEvent, SpinlockMutex};
+
+static ACQUISITIONS: AtomicUsize = AtomicUsize::new(0);
+
+fn count_acquisitions(_lock: usize, event: LockEvent) {
+    if event == LockEvent::Acquired {
+        ACQUISITIONS.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Default)]
+struct Position {
+    x: i32,
+    y: i32,
+    z: i32,
+}
+
+#[test]
+fn commit_applies_all_updates_in_one_critical_section() {
+    set_trace_hook(count_acquisitions);
+    let mutex: SpinlockMutex<0, Position> = SpinlockMutex::new(Position::default());
+
+    let builder = mutex
+        .update_builder()
+        .then(|p| p.x = 1)
+        .then(|p| p.y = 2)
+        .then(|p| p.z = p.x + p.y);
+    assert_eq!(ACQUISITIONS.load(Ordering::Relaxed), 0);
+
+    builder.commit();
+    assert_eq!(ACQUISITIONS.load(Ordering::Relaxed), 1);
+
+    let position = mutex.try_lock().unwrap();
+    assert_eq!((position.x, position.y, position.z), (1, 2, 3));
+}
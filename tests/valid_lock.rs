@@ -0,0 +1,45 @@
+//! Host tests of wrappers generic over the spinlock number.
+
+use rp_spinlockmutex::{LockNumber, SpinlockMutex, ValidLock};
+
+struct Counter<const N: usize>
+where
+    LockNumber<N>: ValidLock,
+{
+    count: SpinlockMutex<N, u32>,
+}
+
+impl<const N: usize> Counter<N>
+where
+    LockNumber<N>: ValidLock,
+{
+    const fn new() -> Self {
+        Self {
+            count: SpinlockMutex::new(0),
+        }
+    }
+
+    fn increment(&self) -> u32 {
+        let mut count = self.count.lock();
+        *count += 1;
+        *count
+    }
+
+    fn is_locked(&self) -> bool {
+        self.count.try_lock().is_none()
+    }
+}
+
+#[test]
+fn generic_wrappers_use_the_given_spinlock() {
+    let first: Counter<0> = Counter::new();
+    let second: Counter<1> = Counter::new();
+
+    assert_eq!(first.increment(), 1);
+    assert_eq!(first.increment(), 2);
+    assert_eq!(second.increment(), 1);
+
+    let _held = first.count.lock();
+    assert!(first.is_locked());
+    assert!(!second.is_locked());
+}